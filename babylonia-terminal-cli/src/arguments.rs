@@ -1,8 +1,13 @@
-use clap::Parser;
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Pass launch options to tinker the behavior of the game, this parameter have priotiy over the
     /// --set-options param
     #[arg(long)]
@@ -13,10 +18,158 @@ pub struct Args {
     #[arg(long)]
     pub set_options: Option<String>,
 
-    /// Pass environment variables to tinker the behavior of the game
+    /// Start the game with gamemoderun (true or false)
+    #[arg(long, value_name = "BOOL")]
+    pub set_gamemode: Option<bool>,
+
+    /// Start the game with mangohud (true or false)
+    #[arg(long, value_name = "BOOL")]
+    pub set_mangohud: Option<bool>,
+
+    /// Start the game inside gamescope with these arguments, like "-W 1920 -H 1080 -f", or off to
+    /// disable it
+    #[arg(long, value_name = "ARGS", allow_hyphen_values = true)]
+    pub set_gamescope: Option<String>,
+
+    /// Set the arguments passed to the game, like "-popupwindow", an empty string remove them
+    #[arg(long, value_name = "ARGS", allow_hyphen_values = true)]
+    pub set_game_args: Option<String>,
+
+    /// Set the render API used by the game, d3d11 (DXVK) or d3d12 (VKD3D-Proton), VKD3D-Proton will
+    /// be installed on the next launch if it's needed
+    #[arg(long, value_name = "API")]
+    pub set_render_api: Option<String>,
+
+    /// Set the window mode of the game : fullscreen, borderless, windowed, or default to let the
+    /// game choose
+    #[arg(long, value_name = "MODE")]
+    pub set_window_mode: Option<String>,
+
+    /// Set the resolution of the game, like 1920x1080, or default
+    #[arg(long, value_name = "SIZE")]
+    pub set_resolution: Option<String>,
+
+    /// Set the monitor used by the game, starting from 1, or default
+    #[arg(long, value_name = "MONITOR")]
+    pub set_monitor: Option<String>,
+
+    /// Start the game in a window without decoration, only with the windowed mode (true or false)
+    #[arg(long, value_name = "BOOL")]
+    pub set_popup_window: Option<bool>,
+
+    /// Set the DXVK HUD shown in the game, like "fps,frametimes", or off to hide it
+    #[arg(long, value_name = "HUD")]
+    pub set_dxvk_hud: Option<String>,
+
+    /// Limit the frame rate of the game, or off to remove the limit
+    #[arg(long, value_name = "FPS")]
+    pub set_frame_limit: Option<String>,
+
+    /// Set dxgi.maxFrameLatency of DXVK, from 1 to 16, or default
+    #[arg(long, value_name = "FRAMES")]
+    pub set_max_frame_latency: Option<String>,
+
+    /// Set an option of the dxvk.conf used by the game, like dxgi.syncInterval=0, can be used
+    /// multiple times
+    #[arg(long, value_name = "OPTION", action = clap::ArgAction::Append)]
+    pub set_dxvk_option: Vec<String>,
+
+    /// Remove an option of the dxvk.conf used by the game, can be used multiple times
+    #[arg(long, value_name = "NAME", action = clap::ArgAction::Append)]
+    pub unset_dxvk_option: Vec<String>,
+
+    /// Set the runner used to start the game : bundled, system, proton:<path to a Proton build>,
+    /// umu, umu:<path to a Proton build> or custom:<program> <args>, the args are split like in a
+    /// shell
+    #[arg(long, value_name = "RUNNER")]
+    pub set_runner: Option<String>,
+
+    /// Set the synchronization used by wine : wineserver, esync, fsync, ntsync, or default to
+    /// keep the one of the runner
+    #[arg(long, value_name = "SYNC")]
+    pub set_sync: Option<String>,
+
+    /// Set the graphics driver used by wine : x11, wayland, or default to keep the one of the
+    /// runner
+    #[arg(long, value_name = "DRIVER")]
+    pub set_graphics_driver: Option<String>,
+
+    /// Set the winetricks script to use, by default the one in your $PATH is used or a copy is
+    /// downloaded in the config directory
+    #[arg(long, value_name = "PATH")]
+    pub set_winetricks: Option<PathBuf>,
+
+    /// Add a winetricks verb to install in the wine prefix, it will be installed on the next
+    /// launch, can be used multiple times
+    #[arg(long, value_name = "VERB", action = clap::ArgAction::Append)]
+    pub add_verb: Vec<String>,
+
+    /// Add a font to install in the wine prefix, like arial or a winetricks font verb such as
+    /// cjkfonts or fakejapanese for the Asian languages, can be used multiple times
+    #[arg(long, value_name = "FONT", action = clap::ArgAction::Append)]
+    pub add_font: Vec<String>,
+
+    /// Add the CJK fonts to install in the wine prefix, for the Chinese, Japanese and Korean
+    /// languages of the game
+    #[arg(long, default_value = "false")]
+    pub add_cjk_fonts: bool,
+
+    /// Set the Windows version of the wine prefix : win7, win8, win81, win10, win11, or default to
+    /// keep the one set by wine
+    #[arg(long, value_name = "VERSION")]
+    pub set_windows_version: Option<String>,
+
+    /// Set the DPI of the wine prefix, or default
+    #[arg(long, value_name = "DPI")]
+    pub set_dpi: Option<String>,
+
+    /// Set the size of the wine virtual desktop, like 1920x1080, or off to disable it
+    #[arg(long, value_name = "SIZE")]
+    pub set_virtual_desktop: Option<String>,
+
+    /// Set the dll overrides of the wine prefix, with the format of WINEDLLOVERRIDES like
+    /// "d3dcompiler_47=n;mscoree=", an empty string remove all of them
+    #[arg(long, value_name = "OVERRIDES")]
+    pub set_dll_overrides: Option<String>,
+
+    /// Save the wine prefix to a tar.gz archive and exit
+    #[arg(long, value_name = "FILE")]
+    pub backup_prefix: Option<PathBuf>,
+
+    /// Replace the wine prefix by a backup made with --backup-prefix and exit
+    #[arg(long, value_name = "FILE")]
+    pub restore_prefix: Option<PathBuf>,
+
+    /// Recreate the wine prefix, reinstall DXVK, the fonts and the dependencies in it and exit
+    #[arg(long, default_value = "false")]
+    pub reset_prefix: bool,
+
+    /// Pass environment variables to tinker the behavior of the game, like NAME=value, they
+    /// override the ones set with the env command
     #[arg(long, value_name = "VALUE", action = clap::ArgAction::Append)]
     pub add_env_var: Vec<String>,
 
+    /// Set how many launches of the game keep their logs
+    #[arg(long, value_name = "COUNT")]
+    pub set_log_retention: Option<usize>,
+
+    /// Copy the Player.log of Unity with the logs of each launch (true or false)
+    #[arg(long, value_name = "BOOL")]
+    pub set_unity_log: Option<bool>,
+
+    /// Set a shell command run before each launch, the game is not started if it fails, an empty
+    /// string remove it
+    #[arg(long, value_name = "COMMAND", allow_hyphen_values = true)]
+    pub set_pre_launch_hook: Option<String>,
+
+    /// Set a shell command run after the game exits, an empty string remove it
+    #[arg(long, value_name = "COMMAND", allow_hyphen_values = true)]
+    pub set_post_exit_hook: Option<String>,
+
+    /// Print the command and the environment used to start the game without starting it
+    #[arg(long, default_value = "false")]
+    pub dry_run: bool,
+
     /// Show the logs direcly to the stdout of your terminal
     #[arg(long, default_value = "false")]
     pub logs: bool,
@@ -29,3 +182,80 @@ pub struct Args {
     #[arg(long, default_value = "false")]
     pub debug: bool,
 }
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Manage the environment variables passed to the game
+    Env {
+        #[command(subcommand)]
+        action: EnvCommand,
+    },
+    /// Add or remove the game in the application menu of your desktop
+    DesktopEntry {
+        #[command(subcommand)]
+        action: DesktopEntryCommand,
+    },
+    /// Add or remove the game in the non-Steam games of Steam, Steam needs to be restarted to see
+    /// the change
+    Steam {
+        #[command(subcommand)]
+        action: SteamCommand,
+    },
+    /// Show or change the settings saved by the game in the wine prefix, the game needs to be
+    /// closed
+    Prefs {
+        #[command(subcommand)]
+        action: PrefsCommand,
+    },
+    /// Remove the shader caches of DXVK and VKD3D-Proton, they are rebuilt while playing
+    ClearShaderCache,
+    /// Stop the game, it's killed if it doesn't exit by itself
+    Kill,
+    /// Show the logs kept of the previous launches of the game
+    Logs,
+    /// Show the playtime and the previous launches of the game
+    Stats,
+    /// Write a tar.gz with the config, the versions and the latest logs to attach to a bug report
+    Report {
+        /// By default it's written in the current directory
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum EnvCommand {
+    /// Set a variable, like NAME=value, the value is replaced if it's already set
+    Set { variable: String },
+    /// Remove a variable
+    Unset { name: String },
+    /// Show the variables
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DesktopEntryCommand {
+    /// Add an entry starting the game with this executable
+    Install,
+    /// Remove the entry and its icon
+    Uninstall,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SteamCommand {
+    /// Add a shortcut starting the game with this executable, or update it
+    Add,
+    /// Remove the shortcut
+    Remove,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PrefsCommand {
+    /// Show the settings
+    List,
+    /// Set a setting, the value keeps the type of the previous one
+    Set { name: String, value: String },
+    /// Remove a setting, the game uses its default value next time
+    Unset { name: String },
+    /// Remove the resolution, the window mode and the quality, when the game can't start anymore
+    ResetGraphics,
+}
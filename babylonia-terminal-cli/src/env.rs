@@ -0,0 +1,39 @@
+use babylonia_terminal_sdk::{game_config::GameConfig, game_manager::EnvironmentVariable};
+use log::{error, info};
+
+use crate::arguments::EnvCommand;
+
+pub async fn run(action: EnvCommand) {
+    match action {
+        EnvCommand::Set { variable } => {
+            let variable = match EnvironmentVariable::parse(&variable) {
+                Ok(v) => v,
+                Err(e) => {
+                    error!("{}", e);
+                    return;
+                }
+            };
+
+            let name = variable.name.clone();
+            match GameConfig::set_env_var(variable).await {
+                Ok(_) => info!("{} set", name),
+                Err(e) => error!("Failed to save the environment variable : {}", e),
+            }
+        }
+        EnvCommand::Unset { name } => match GameConfig::unset_env_var(&name).await {
+            Ok(true) => info!("{} unset", name),
+            Ok(false) => info!("{} was not set", name),
+            Err(e) => error!("Failed to save the environment variable : {}", e),
+        },
+        EnvCommand::List => {
+            let env_vars = GameConfig::get_env_vars().await;
+            if env_vars.is_empty() {
+                info!("No environment variable set");
+            }
+
+            for (name, value) in env_vars {
+                info!("{}={}", name, value);
+            }
+        }
+    }
+}
@@ -3,14 +3,15 @@ use std::{path::PathBuf, str::FromStr, sync::Arc};
 use babylonia_terminal_sdk::{
     components::{
         dxvk_component::{DXVK_DEV, DXVK_REPO},
+        vkd3d_component::{VKD3D_DEV, VKD3D_REPO},
         wine_component::{WineComponent, WINE_DEV, WINE_REPO},
     },
-    game_config::GameConfig,
+    game_config::{GameConfig, RenderApi},
     game_manager::{EnvironmentVariable, GameManager},
     game_state::GameState,
 };
 
-use log::{debug, info};
+use log::{debug, error, info};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use wincompatlib::prelude::*;
 
@@ -58,10 +59,7 @@ pub async fn run(
 
         if state != GameState::WineNotInstalled && wine == None {
             let wine_component = WineComponent::new(GameConfig::get_config_directory().await);
-            match wine_component.init_wine() {
-                Ok(p) => wine = Some(p),
-                Err(err) => panic!("{}", err),
-            };
+            wine = Some(wine_component.init_wine(&GameConfig::get_runner().await));
         }
 
         match state {
@@ -93,6 +91,15 @@ pub async fn run(
                 );
                 info!("wine installed");
             }
+            GameState::PrefixNotInitialized => {
+                GameManager::init_prefix(
+                    &wine.clone().unwrap(),
+                    Some(DownloadReporter::create(false)),
+                )
+                .await
+                .expect("Failed to initialize the wine prefix");
+                info!("Wine prefix initialized");
+            }
             GameState::DXVKNotInstalled => {
                 let release;
                 if utils::use_latest(
@@ -192,9 +199,38 @@ pub async fn run(
         }
     }
 
+    let config = GameConfig::get_config().await;
+    if config.render_api == RenderApi::D3D12 && !config.is_vkd3d_installed {
+        let release;
+        if utils::use_latest(
+            "Do you want to install latest version of VKD3D-Proton or a specific version of it?",
+        ) {
+            release = 0;
+        } else {
+            release = utils::choose_release_version(
+                VKD3D_DEV,
+                VKD3D_REPO,
+                "Please, select a version of VKD3D-Proton to install.",
+            )
+            .await
+            .expect("Failed to fetch VKD3D-Proton version!");
+        }
+
+        info!("VKD3D-Proton not installed, installing it...");
+        GameManager::install_vkd3d(
+            &wine.clone().unwrap(),
+            GameConfig::get_config_directory().await,
+            release,
+            Some(DownloadReporter::create(false)),
+        )
+        .await
+        .expect("Failed to installed VKD3D-Proton");
+        info!("VKD3D-Proton installed");
+    }
+
     info!("Starting game...");
     debug!("{:?}", wine);
-    GameManager::start_game(
+    match GameManager::start_game(
         &wine.unwrap(),
         GameConfig::get_game_dir()
             .await
@@ -203,5 +239,50 @@ pub async fn run(
         env_vars,
         show_logs,
     )
-    .await;
+    .await
+    {
+        Ok(status) if status.success() => info!("Game exited"),
+        Ok(status) => error!("Game exited with {}", status),
+        Err(e) => error!("Failed to start the game : {}", e),
+    }
+}
+
+pub async fn kill() {
+    info!("Stopping the game...");
+    match GameManager::stop_game().await {
+        Ok(true) => info!("Game stopped"),
+        Ok(false) => info!("The game is not running"),
+        Err(e) => error!("Failed to stop the game : {}", e),
+    }
+}
+
+pub async fn clear_shader_cache() {
+    match GameManager::clear_shader_cache().await {
+        Ok(0) => info!("The shader cache was already empty"),
+        Ok(count) => info!("{} shader cache file(s) removed", count),
+        Err(e) => error!("Failed to clear the shader cache : {}", e),
+    }
+}
+
+pub async fn dry_run(launch_options: Option<String>, env_vars: Vec<EnvironmentVariable>) {
+    let Some(game_dir) = GameConfig::get_game_dir().await else {
+        error!("The game directory was not found, the game needs to be installed first");
+        return;
+    };
+
+    let wine = WineComponent::new(GameConfig::get_config_directory().await)
+        .init_wine(&GameConfig::get_runner().await);
+
+    match GameManager::prepare_launch(&wine, game_dir, launch_options, env_vars).await {
+        Ok(prepared) => {
+            println!("Command : {}", prepared.get_command_line());
+            println!("Working directory : {}", prepared.working_dir.display());
+            println!("Environment :\n{}", prepared.env.dump());
+            println!(
+                "\nTo start it from a terminal :\n{}",
+                prepared.to_shell_command()
+            );
+        }
+        Err(e) => error!("Failed to prepare the launch : {}", e),
+    }
 }
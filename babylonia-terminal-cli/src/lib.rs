@@ -1,11 +1,28 @@
-use arguments::Args;
-use babylonia_terminal_sdk::{game_config::GameConfig, game_manager::EnvironmentVariable};
+use std::str::FromStr;
+
+use arguments::{Args, Command};
+use babylonia_terminal_sdk::{
+    components::wine_component::WineComponent,
+    game_config::{GameConfig, RenderApi},
+    game_manager::EnvironmentVariable,
+    launch_options::LaunchOptions,
+    prefix_manifest::CJK_FONTS,
+    prefix_settings::{check_dpi, PrefixSettings, VirtualDesktop, WindowsVersion},
+    runner::Runner,
+    runner_settings::{GraphicsDriver, SyncMode},
+    unity_player::{Resolution, WindowMode},
+};
 use clap::Parser;
-use log::debug;
+use log::{debug, error, info};
 
 pub mod arguments;
+pub mod env;
 pub mod game;
+pub mod logs;
+pub mod prefix;
+pub mod prefs;
 pub mod reporter;
+pub mod shortcuts;
 pub mod utils;
 
 pub fn run() {
@@ -18,18 +35,358 @@ pub fn run() {
         .build()
         .unwrap()
         .block_on(async {
-            if let Some(command) = args.set_options {
-                GameConfig::set_launch_options(Some(command))
+            match args.command {
+                Some(Command::Env { action }) => {
+                    env::run(action).await;
+                    return;
+                }
+                Some(Command::DesktopEntry { action }) => {
+                    shortcuts::desktop_entry(action).await;
+                    return;
+                }
+                Some(Command::Steam { action }) => {
+                    shortcuts::steam(action).await;
+                    return;
+                }
+                Some(Command::Prefs { action }) => {
+                    prefs::run(action).await;
+                    return;
+                }
+                Some(Command::ClearShaderCache) => {
+                    game::clear_shader_cache().await;
+                    return;
+                }
+                Some(Command::Kill) => {
+                    game::kill().await;
+                    return;
+                }
+                Some(Command::Logs) => {
+                    logs::list().await;
+                    return;
+                }
+                Some(Command::Stats) => {
+                    logs::stats().await;
+                    return;
+                }
+                Some(Command::Report { output }) => {
+                    logs::report(output).await;
+                    return;
+                }
+                None => {}
+            }
+
+            if args.set_options.is_some()
+                || args.set_gamemode.is_some()
+                || args.set_mangohud.is_some()
+                || args.set_gamescope.is_some()
+                || args.set_game_args.is_some()
+            {
+                let mut launch_options = GameConfig::get_launch_options().await;
+
+                if let Some(command) = args.set_options {
+                    launch_options.raw = Some(command).filter(|c| !c.trim().is_empty());
+                }
+
+                if let Some(gamemode) = args.set_gamemode {
+                    launch_options.gamemode = gamemode;
+                }
+
+                if let Some(mangohud) = args.set_mangohud {
+                    launch_options.mangohud = mangohud;
+                }
+
+                if let Some(gamescope) = args.set_gamescope {
+                    launch_options.gamescope = match gamescope.as_str() {
+                        "off" => None,
+                        _ => Some(
+                            LaunchOptions::parse_args(&gamescope)
+                                .expect("Invalid gamescope arguments"),
+                        ),
+                    };
+                }
+
+                if let Some(game_args) = args.set_game_args {
+                    launch_options.game_args =
+                        LaunchOptions::parse_args(&game_args).expect("Invalid game arguments");
+                }
+
+                GameConfig::set_launch_options(launch_options)
                     .await
                     .expect("Failed to save launch options into the config file");
             }
 
+            if let Some(render_api) = args.set_render_api {
+                let render_api = RenderApi::from_str(&render_api).expect("Invalid render API");
+                GameConfig::set_render_api(render_api)
+                    .await
+                    .expect("Failed to save the render API into the config file");
+            }
+
+            if args.set_window_mode.is_some()
+                || args.set_resolution.is_some()
+                || args.set_monitor.is_some()
+                || args.set_popup_window.is_some()
+            {
+                let mut settings = GameConfig::get_unity_settings().await;
+
+                if let Some(mode) = args.set_window_mode {
+                    settings.window_mode = match mode.as_str() {
+                        "default" => None,
+                        _ => Some(WindowMode::from_str(&mode).expect("Invalid window mode")),
+                    };
+                }
+
+                if let Some(resolution) = args.set_resolution {
+                    settings.resolution = match resolution.as_str() {
+                        "default" => None,
+                        _ => Some(Resolution::from_str(&resolution).expect("Invalid resolution")),
+                    };
+                }
+
+                if let Some(monitor) = args.set_monitor {
+                    settings.monitor = match monitor.as_str() {
+                        "default" => None,
+                        _ => Some(monitor.parse::<u32>().expect("Invalid monitor")),
+                    };
+                }
+
+                match args.set_popup_window {
+                    Some(popup_window) => settings.popup_window = popup_window,
+                    // the popup window can't be used with the other modes
+                    None if settings
+                        .window_mode
+                        .is_some_and(|m| m != WindowMode::Windowed) =>
+                    {
+                        settings.popup_window = false
+                    }
+                    None => {}
+                }
+
+                if let Err(e) = GameConfig::set_unity_settings(settings).await {
+                    error!("Failed to save the window settings : {}", e);
+                    return;
+                }
+            }
+
+            if args.set_dxvk_hud.is_some()
+                || args.set_frame_limit.is_some()
+                || args.set_max_frame_latency.is_some()
+                || !args.set_dxvk_option.is_empty()
+                || !args.unset_dxvk_option.is_empty()
+            {
+                let mut settings = GameConfig::get_dxvk_settings().await;
+
+                if let Some(hud) = args.set_dxvk_hud {
+                    settings.hud = Some(hud).filter(|h| h != "off" && !h.trim().is_empty());
+                }
+
+                if let Some(limit) = args.set_frame_limit {
+                    settings.frame_limit = match limit.as_str() {
+                        "off" => None,
+                        _ => Some(limit.parse::<u32>().expect("Invalid frame limit")),
+                    };
+                }
+
+                if let Some(latency) = args.set_max_frame_latency {
+                    settings.max_frame_latency = match latency.as_str() {
+                        "default" => None,
+                        _ => Some(latency.parse::<u32>().expect("Invalid frame latency")),
+                    };
+                }
+
+                for option in args.set_dxvk_option {
+                    let (name, value) = option
+                        .split_once('=')
+                        .expect("Invalid DXVK option, expected NAME=value");
+                    settings.set_option(name.trim(), value.trim());
+                }
+
+                for name in args.unset_dxvk_option {
+                    if !settings.unset_option(&name) {
+                        info!("{} was not set", name);
+                    }
+                }
+
+                GameConfig::set_dxvk_settings(settings)
+                    .await
+                    .expect("Failed to save the DXVK settings into the config file");
+            }
+
+            if args.set_log_retention.is_some() || args.set_unity_log.is_some() {
+                let mut settings = GameConfig::get_log_settings().await;
+
+                if let Some(retention) = args.set_log_retention {
+                    if retention == 0 {
+                        error!("The logs of at least one launch need to be kept");
+                        return;
+                    }
+                    settings.retention = retention;
+                }
+
+                if let Some(collect) = args.set_unity_log {
+                    settings.collect_unity_log = collect;
+                }
+
+                GameConfig::set_log_settings(settings)
+                    .await
+                    .expect("Failed to save the log settings into the config file");
+            }
+
+            if args.set_pre_launch_hook.is_some() || args.set_post_exit_hook.is_some() {
+                let mut hooks = GameConfig::get_hooks().await;
+
+                if let Some(command) = args.set_pre_launch_hook {
+                    hooks.pre_launch = Some(command).filter(|c| !c.trim().is_empty());
+                }
+
+                if let Some(command) = args.set_post_exit_hook {
+                    hooks.post_exit = Some(command).filter(|c| !c.trim().is_empty());
+                }
+
+                GameConfig::set_hooks(hooks)
+                    .await
+                    .expect("Failed to save the hooks into the config file");
+            }
+
+            if let Some(runner) = args.set_runner {
+                let runner = Runner::from_str(&runner).expect("Invalid runner");
+                GameConfig::set_runner(runner)
+                    .await
+                    .expect("Failed to save the runner into the config file");
+            }
+
+            if args.set_sync.is_some() || args.set_graphics_driver.is_some() {
+                let mut settings = GameConfig::get_runner_settings().await;
+
+                if let Some(sync) = args.set_sync {
+                    settings.sync = match sync.as_str() {
+                        "default" => None,
+                        _ => Some(SyncMode::from_str(&sync).expect("Invalid synchronization")),
+                    };
+                }
+
+                if let Some(driver) = args.set_graphics_driver {
+                    settings.graphics_driver = match driver.as_str() {
+                        "default" => None,
+                        _ => Some(
+                            GraphicsDriver::from_str(&driver).expect("Invalid graphics driver"),
+                        ),
+                    };
+                }
+
+                let runner = GameConfig::get_runner().await;
+                let wine_version = WineComponent::new(GameConfig::get_config_directory().await)
+                    .init_wine(&runner)
+                    .version()
+                    .ok();
+                settings
+                    .check(&runner, wine_version.as_ref().and_then(|v| v.to_str()))
+                    .expect("Unsupported runner settings");
+
+                GameConfig::set_runner_settings(settings)
+                    .await
+                    .expect("Failed to save the runner settings into the config file");
+            }
+
+            if let Some(path) = args.set_winetricks {
+                GameConfig::set_winetricks_path(Some(path))
+                    .await
+                    .expect("Failed to save the winetricks path into the config file");
+            }
+
+            for verb in args.add_verb {
+                if !GameConfig::add_winetricks_verb(&verb)
+                    .await
+                    .expect("Failed to save the winetricks verb into the config file")
+                {
+                    info!("{} is already in the list of dependencies", verb);
+                }
+            }
+
+            let mut fonts = args.add_font;
+            if args.add_cjk_fonts {
+                fonts.extend(CJK_FONTS.map(String::from));
+            }
+
+            for font in fonts {
+                if !GameConfig::add_font(&font)
+                    .await
+                    .expect("Failed to save the font into the config file")
+                {
+                    info!("{} is already in the list of fonts", font);
+                }
+            }
+
+            if args.set_windows_version.is_some()
+                || args.set_dpi.is_some()
+                || args.set_virtual_desktop.is_some()
+                || args.set_dll_overrides.is_some()
+            {
+                let mut settings = GameConfig::get_prefix_settings().await;
+
+                if let Some(version) = args.set_windows_version {
+                    settings.windows_version = match version.as_str() {
+                        "default" => None,
+                        _ => Some(
+                            WindowsVersion::from_str(&version).expect("Invalid Windows version"),
+                        ),
+                    };
+                }
+
+                if let Some(dpi) = args.set_dpi {
+                    settings.dpi = match dpi.as_str() {
+                        "default" => None,
+                        _ => {
+                            let dpi = dpi.parse::<u32>().expect("Invalid DPI");
+                            check_dpi(dpi).expect("Invalid DPI");
+                            Some(dpi)
+                        }
+                    };
+                }
+
+                if let Some(size) = args.set_virtual_desktop {
+                    settings.virtual_desktop = match size.as_str() {
+                        "off" => None,
+                        _ => Some(
+                            VirtualDesktop::from_str(&size).expect("Invalid virtual desktop size"),
+                        ),
+                    };
+                }
+
+                if let Some(overrides) = args.set_dll_overrides {
+                    settings.dll_overrides = PrefixSettings::parse_dll_overrides(&overrides)
+                        .expect("Invalid dll overrides");
+                }
+
+                prefix::set_settings(settings).await;
+            }
+
+            if let Some(output_file) = args.backup_prefix {
+                prefix::backup(output_file).await;
+                return;
+            }
+
+            if let Some(backup_file) = args.restore_prefix {
+                prefix::restore(backup_file).await;
+                return;
+            }
+
+            if args.reset_prefix {
+                prefix::reset().await;
+                return;
+            }
+
             let vars = args
                 .add_env_var
                 .iter()
-                .map(|v| EnvironmentVariable::parse(v))
+                .map(|v| EnvironmentVariable::parse(v).expect("Invalid environment variable"))
                 .collect();
 
+            if args.dry_run {
+                game::dry_run(args.options, vars).await;
+                return;
+            }
+
             game::run(args.options, vars, args.logs).await;
         });
 }
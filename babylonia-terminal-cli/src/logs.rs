@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+
+use babylonia_terminal_sdk::{
+    components::wine_component::WineComponent,
+    game_config::GameConfig,
+    game_logs::LogSession,
+    game_manager::GameManager,
+    playtime::{format_playtime, PlaytimeHistory},
+};
+use log::{error, info};
+
+pub async fn list() {
+    let sessions = match LogSession::list().await {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            error!("Failed to read the logs : {}", e);
+            return;
+        }
+    };
+
+    if sessions.is_empty() {
+        info!("No logs kept, the game was not started yet");
+    }
+
+    for session in sessions {
+        let exit = match (session.info.ended_at, session.info.exit_code) {
+            (None, _) => "still running or interrupted".to_string(),
+            (Some(_), Some(code)) => format!("exit code {}", code),
+            (Some(_), None) => "killed".to_string(),
+        };
+
+        info!(
+            "{} ({}) -> {}",
+            session.info.started_at.format("%Y-%m-%d %H:%M:%S"),
+            exit,
+            session.path.display()
+        );
+    }
+}
+
+// the latest launches shown with the stats
+static STATS_SESSIONS_COUNT: usize = 10;
+
+pub async fn stats() {
+    let history = match PlaytimeHistory::load().await {
+        Ok(history) => history,
+        Err(e) => {
+            error!("Failed to read the playtime : {}", e);
+            return;
+        }
+    };
+
+    let Some(last_session) = history.get_last_session() else {
+        info!("The game was not started yet");
+        return;
+    };
+
+    info!(
+        "Total playtime : {} in {} launches",
+        format_playtime(history.get_total_playtime()),
+        history.sessions.len()
+    );
+    info!(
+        "Last played : {}",
+        last_session.started_at.format("%Y-%m-%d %H:%M")
+    );
+
+    for session in history.sessions.iter().rev().take(STATS_SESSIONS_COUNT) {
+        info!(
+            "{} -> {} ({}, version {})",
+            session.started_at.format("%Y-%m-%d %H:%M"),
+            session
+                .get_duration()
+                .map(format_playtime)
+                .unwrap_or_else(|| "interrupted".to_string()),
+            match session.exit_code {
+                Some(code) => format!("exit code {}", code),
+                None => "killed".to_string(),
+            },
+            session.game_version.as_deref().unwrap_or("unknown")
+        );
+    }
+}
+
+pub async fn report(output: Option<PathBuf>) {
+    let output = output.unwrap_or_else(|| PathBuf::from("babylonia-terminal-report.tar.gz"));
+
+    let config = GameConfig::get_config().await;
+    let wine = config
+        .is_wine_installed
+        .then(|| WineComponent::new(config.config_dir.clone()).init_wine(&config.runner));
+
+    info!("Writing the report, the game files are checked, this can take a while...");
+    match GameManager::export_diagnostics(wine.as_ref(), output.clone()).await {
+        Ok(_) => info!("Report written to {}", output.display()),
+        Err(e) => error!("Failed to write the report : {}", e),
+    }
+}
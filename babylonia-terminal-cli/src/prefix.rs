@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+
+use babylonia_terminal_sdk::{
+    components::wine_component::WineComponent, game_config::GameConfig, game_manager::GameManager,
+    prefix_settings::PrefixSettings,
+};
+use log::{error, info};
+use wincompatlib::prelude::Wine;
+
+use crate::reporter::DownloadReporter;
+
+pub async fn backup(output_file: PathBuf) {
+    match GameManager::backup_prefix(output_file.clone()).await {
+        Ok(_) => info!("Wine prefix saved to {}", output_file.to_str().unwrap()),
+        Err(e) => error!("Failed to backup the wine prefix : {}", e),
+    }
+}
+
+pub async fn restore(backup_file: PathBuf) {
+    match GameManager::restore_prefix(backup_file, Some(DownloadReporter::create(false))).await {
+        Ok(_) => info!("Wine prefix restored"),
+        Err(e) => error!("Failed to restore the wine prefix : {}", e),
+    }
+}
+
+pub async fn reset() {
+    if !GameConfig::get_config().await.is_wine_installed {
+        error!("Wine is not installed, there's no prefix to reset");
+        return;
+    }
+
+    let wine = get_wine().await;
+
+    match GameManager::reset_prefix(&wine, Some(DownloadReporter::create(false))).await {
+        Ok(_) => info!("Wine prefix reset"),
+        Err(e) => error!("Failed to reset the wine prefix : {}", e),
+    }
+}
+
+pub async fn set_settings(settings: PrefixSettings) {
+    let config = GameConfig::get_config().await;
+
+    // without a prefix, the settings are applied once it's created
+    let wine = Some(get_wine().await).filter(|wine| {
+        config.is_wine_installed && WineComponent::is_prefix_initialized(&wine.prefix)
+    });
+
+    match GameManager::set_prefix_settings(wine.as_ref(), settings).await {
+        Ok(_) if wine.is_some() => info!("Prefix settings applied"),
+        Ok(_) => info!("Prefix settings saved, they will be applied on the next launch"),
+        Err(e) => error!("Failed to apply the prefix settings : {}", e),
+    }
+}
+
+async fn get_wine() -> Wine {
+    let wine_component = WineComponent::new(GameConfig::get_config_directory().await);
+    wine_component.init_wine(&GameConfig::get_runner().await)
+}
@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+
+use babylonia_terminal_sdk::{
+    game_config::GameConfig,
+    game_manager::GameManager,
+    player_prefs::{PlayerPrefs, PrefValue},
+};
+use log::{error, info};
+
+use crate::arguments::PrefsCommand;
+
+pub async fn run(action: PrefsCommand) {
+    let Some(game_dir) = GameConfig::get_game_dir().await else {
+        error!("The game directory was not found, the game needs to be installed first");
+        return;
+    };
+
+    match action {
+        PrefsCommand::List => {
+            let Some(prefs) = load(game_dir).await else {
+                return;
+            };
+
+            let list = prefs.list();
+            if list.is_empty() {
+                info!("No setting was saved by the game, it needs to be started once");
+            }
+
+            for pref in list {
+                info!("{} = {}", pref.name, pref.value);
+            }
+        }
+        PrefsCommand::Set { name, value } => {
+            let result = GameManager::edit_player_prefs(game_dir, |prefs| {
+                let previous = prefs.get(&name).map(|p| p.value);
+                let value = PrefValue::parse(&value, previous.as_ref())?;
+                prefs.set(&name, &value)
+            })
+            .await;
+
+            match result {
+                Ok(_) => info!("{} set", name),
+                Err(e) => error!("Failed to set {} : {}", name, e),
+            }
+        }
+        PrefsCommand::Unset { name } => {
+            match GameManager::edit_player_prefs(game_dir, |prefs| Ok(prefs.remove(&name))).await {
+                Ok(true) => info!("{} unset", name),
+                Ok(false) => info!("{} was not set", name),
+                Err(e) => error!("Failed to unset {} : {}", name, e),
+            }
+        }
+        PrefsCommand::ResetGraphics => match GameManager::reset_graphics_settings(game_dir).await {
+            Ok(0) => info!("No graphics setting was saved by the game"),
+            Ok(count) => info!("{} graphics setting(s) removed", count),
+            Err(e) => error!("Failed to reset the graphics settings : {}", e),
+        },
+    }
+}
+
+async fn load(game_dir: PathBuf) -> Option<PlayerPrefs> {
+    match GameManager::get_player_prefs(game_dir).await {
+        Ok(prefs) => Some(prefs),
+        Err(e) => {
+            error!("Failed to read the settings of the game : {}", e);
+            None
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use babylonia_terminal_sdk::{desktop_entry, steam_shortcut};
+use log::{error, info};
+
+use crate::arguments::{DesktopEntryCommand, SteamCommand};
+
+pub async fn desktop_entry(action: DesktopEntryCommand) {
+    match action {
+        DesktopEntryCommand::Install => {
+            let executable = match std::env::current_exe() {
+                Ok(path) => path,
+                Err(e) => {
+                    error!("Failed to find the path of the launcher : {}", e);
+                    return;
+                }
+            };
+
+            match desktop_entry::install(&executable).await {
+                Ok(_) => info!("The game was added to the application menu"),
+                Err(e) => error!("Failed to add the game to the application menu : {}", e),
+            }
+        }
+        DesktopEntryCommand::Uninstall => match desktop_entry::uninstall().await {
+            Ok(true) => info!("The game was removed from the application menu"),
+            Ok(false) => info!("The game was not in the application menu"),
+            Err(e) => error!(
+                "Failed to remove the game from the application menu : {}",
+                e
+            ),
+        },
+    }
+}
+
+pub async fn steam(action: SteamCommand) {
+    match action {
+        SteamCommand::Add => {
+            let executable = match std::env::current_exe() {
+                Ok(path) => path,
+                Err(e) => {
+                    error!("Failed to find the path of the launcher : {}", e);
+                    return;
+                }
+            };
+
+            match steam_shortcut::add_shortcut(&executable).await {
+                Ok(0) => info!("No Steam user was found, log in Steam once and retry"),
+                Ok(count) => info!(
+                    "The game was added to Steam for {} user(s), restart Steam to see it",
+                    count
+                ),
+                Err(e) => error!("Failed to add the game to Steam : {}", e),
+            }
+        }
+        SteamCommand::Remove => match steam_shortcut::remove_shortcut().await {
+            Ok(0) => info!("The game was not in Steam"),
+            Ok(count) => info!(
+                "The game was removed from Steam for {} user(s), restart Steam to see the change",
+                count
+            ),
+            Err(e) => error!("Failed to remove the game from Steam : {}", e),
+        },
+    }
+}
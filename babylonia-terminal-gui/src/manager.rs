@@ -1,15 +1,12 @@
-use std::{ops::Deref, sync::Arc};
+use std::{ops::Deref, path::PathBuf, sync::Arc};
 
 use babylonia_terminal_sdk::{
     components::wine_component::WineComponent, game_config::GameConfig, game_manager::GameManager,
-    utils::github_requester::GithubRelease,
-};
-use downloader::download;
-use log::{debug, error};
-use relm4::{
-    tokio::{self, sync::OnceCell},
-    Worker,
+    prefix_settings::PrefixSettings, utils::github_requester::GithubRelease,
 };
+use downloader::{download, progress::Noop};
+use log::{debug, error, warn};
+use relm4::{tokio, Worker};
 use wincompatlib::prelude::Wine;
 
 use crate::ui::{
@@ -23,33 +20,37 @@ use crate::ui::{
     },
 };
 
-static WINE: OnceCell<Wine> = OnceCell::const_new();
-
-pub async fn get_wine() -> anyhow::Result<Wine> {
-    if !WINE.initialized() {
-        let wine_component = WineComponent::new(GameConfig::get_config().await.config_dir);
-        let wine = wine_component.init_wine();
+// not cached, the runner can be changed in the settings
+pub async fn get_wine() -> Wine {
+    let config = GameConfig::get_config().await;
+    WineComponent::new(config.config_dir).init_wine(&config.runner)
+}
 
-        if let Err(ref e) = wine {
-            error!("Failed to initialize wine : {}", e);
-            anyhow::bail!("Failed to initialize wine : {}", e);
-        }
+/// Like [`get_wine`], but the prefix is created if it's missing, to install or start something in
+/// it
+pub async fn get_wine_with_prefix() -> anyhow::Result<Wine> {
+    let wine = get_wine().await;
 
-        Ok(WINE.get_or_init(|| async { wine.unwrap() }).await.clone())
-    } else {
-        Ok(WINE.get().unwrap().clone())
+    // the prefix is created during the setup, it's only missing if it was deleted by hand
+    if !WineComponent::is_prefix_initialized(&wine.prefix) {
+        GameManager::init_prefix(&wine, None::<Arc<Noop>>).await?;
     }
+
+    Ok(wine)
 }
 
 pub async fn run_game() -> anyhow::Result<()> {
-    let wine = get_wine().await?;
+    let wine = get_wine_with_prefix().await?;
     let game_dir = GameConfig::get_config().await.game_dir;
     if game_dir.is_none() {
         error!("Failed to start game, the game directory was not found");
         anyhow::bail!("Failed to start game, the game directory was not found");
     }
 
-    GameManager::start_game(&wine, game_dir.unwrap(), None, vec![], false).await?;
+    let status = GameManager::start_game(&wine, game_dir.unwrap(), None, vec![], false).await?;
+    if !status.success() {
+        warn!("Game exited with {}", status);
+    }
 
     Ok(())
 }
@@ -268,11 +269,28 @@ impl Worker for HandleComponentInstallation {
                             GameConfig::get_config_directory().await
                         };
 
-                        if let Err(error) = GameManager::install_wine(game_dir.clone(), wine_release, Some(progress_bar.clone())).await {
-                            sender.output(download_components::DownloadComponentsMsg::ShowError(format!("Failed to install wine : {}", error))).unwrap();
+                        // a new entry in the prefix manifest bring back the setup, the components already installed are kept
+                        let config = GameConfig::get_config().await;
+
+                        if !config.is_wine_installed {
+                            if let Err(error) = GameManager::install_wine(game_dir.clone(), wine_release, Some(progress_bar.clone())).await {
+                                sender.output(download_components::DownloadComponentsMsg::ShowError(format!("Failed to install wine : {}", error))).unwrap();
+                                return;
+                            }
+                        }
+
+                        let _ = sender
+                            .output(download_components::DownloadComponentsMsg::UpdateProgressBarMsg(String::from("Initializing the wine prefix"), None));
+
+                        let wine_component = WineComponent::new(GameConfig::get_config_directory().await);
+                        if let Err(error) = GameManager::init_prefix(&wine_component.init_wine(&config.runner), Some(progress_bar.clone())).await {
+                            sender.output(download_components::DownloadComponentsMsg::ShowError(format!("Failed to initialize the wine prefix : {}", error))).unwrap();
                             return;
                         }
 
+                        // a new prefix need everything to be installed again
+                        let config = GameConfig::get_config().await;
+
                         let _ = sender
                             .output(download_components::DownloadComponentsMsg::UpdateProgressBarMsg(String::from("Starting download for DXVK"), Some(String::from("Installing DXVK"))));
 
@@ -280,7 +298,7 @@ impl Worker for HandleComponentInstallation {
 
                         let _ = sender.output(download_components::DownloadComponentsMsg::UpdateDownloadedComponentName(String::from("DXVK")));
 
-                        let wine = match get_wine().await {
+                        let wine = match get_wine_with_prefix().await {
                             Ok(p) => p,
                             Err(e) => {
                                 sender.output(download_components::DownloadComponentsMsg::ShowError(format!("Failed to initialize wine : {:?}", e))).unwrap();
@@ -288,9 +306,11 @@ impl Worker for HandleComponentInstallation {
                             }
                         };
 
-                        if let Err(error) = GameManager::install_dxvk(&wine, game_dir, dxvk_release, Some(progress_bar.clone())).await {
-                            sender.output(download_components::DownloadComponentsMsg::ShowError(format!("Failed to install DXVK : {}", error))).unwrap();
-                            return;
+                        if !config.is_dxvk_installed {
+                            if let Err(error) = GameManager::install_dxvk(&wine, game_dir, dxvk_release, Some(progress_bar.clone())).await {
+                                sender.output(download_components::DownloadComponentsMsg::ShowError(format!("Failed to install DXVK : {}", error))).unwrap();
+                                return;
+                            }
                         }
 
                         let _ = sender
@@ -325,3 +345,202 @@ impl Worker for HandleComponentInstallation {
         }
     }
 }
+
+#[derive(Debug)]
+pub enum HandleSettingsTaskMsg {
+    InstallVkd3d(usize), // vkd3d release
+    InstallPrefixManifest,
+    BackupPrefix(PathBuf),
+    RestorePrefix(PathBuf),
+    ResetPrefix,
+    ApplyPrefixSettings(PrefixSettings),
+}
+
+#[derive(Debug)]
+pub struct HandleSettingsTask;
+
+impl Worker for HandleSettingsTask {
+    type Init = ();
+
+    type Input = HandleSettingsTaskMsg;
+
+    type Output = pages::settings::SettingsPageMsg;
+
+    fn init(_init: Self::Init, _sender: relm4::ComponentSender<Self>) -> Self {
+        Self
+    }
+
+    fn update(&mut self, message: Self::Input, sender: relm4::ComponentSender<Self>) {
+        match message {
+            HandleSettingsTaskMsg::InstallVkd3d(vkd3d_release) => {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .unwrap()
+                    .block_on(async {
+                        let _ =
+                            sender.output(pages::settings::SettingsPageMsg::SetIsInstalling(true));
+
+                        let wine = match get_wine_with_prefix().await {
+                            Ok(p) => p,
+                            Err(e) => {
+                                let _ = sender.output(pages::settings::SettingsPageMsg::ShowError(
+                                    format!("Failed to initialize wine : {:?}", e),
+                                ));
+                                let _ = sender.output(
+                                    pages::settings::SettingsPageMsg::SetIsInstalling(false),
+                                );
+                                return;
+                            }
+                        };
+
+                        if let Err(error) = GameManager::install_vkd3d(
+                            &wine,
+                            GameConfig::get_config_directory().await,
+                            vkd3d_release,
+                            None::<Arc<Noop>>,
+                        )
+                        .await
+                        {
+                            let _ = sender.output(pages::settings::SettingsPageMsg::ShowError(
+                                format!("Failed to install VKD3D-Proton : {}", error),
+                            ));
+                        }
+
+                        let _ =
+                            sender.output(pages::settings::SettingsPageMsg::SetIsInstalling(false));
+                        let _ = sender.output(pages::settings::SettingsPageMsg::UpdateConfig);
+                    });
+            }
+            HandleSettingsTaskMsg::InstallPrefixManifest => {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .unwrap()
+                    .block_on(async {
+                        let _ =
+                            sender.output(pages::settings::SettingsPageMsg::SetIsInstalling(true));
+
+                        let wine = match get_wine_with_prefix().await {
+                            Ok(p) => p,
+                            Err(e) => {
+                                let _ = sender.output(pages::settings::SettingsPageMsg::ShowError(
+                                    format!("Failed to initialize wine : {:?}", e),
+                                ));
+                                let _ = sender.output(
+                                    pages::settings::SettingsPageMsg::SetIsInstalling(false),
+                                );
+                                return;
+                            }
+                        };
+
+                        if let Err(error) =
+                            GameManager::install_font(&wine, None::<Arc<Noop>>).await
+                        {
+                            let _ = sender.output(pages::settings::SettingsPageMsg::ShowError(
+                                format!("Failed to install the fonts : {}", error),
+                            ));
+                        } else if let Err(error) = GameManager::install_dependencies(&wine).await {
+                            let _ = sender.output(pages::settings::SettingsPageMsg::ShowError(
+                                format!("Failed to install the dependencies : {}", error),
+                            ));
+                        }
+
+                        let _ =
+                            sender.output(pages::settings::SettingsPageMsg::SetIsInstalling(false));
+                        let _ = sender.output(pages::settings::SettingsPageMsg::UpdateConfig);
+                    });
+            }
+            HandleSettingsTaskMsg::BackupPrefix(output_file) => {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .unwrap()
+                    .block_on(async {
+                        let _ =
+                            sender.output(pages::settings::SettingsPageMsg::SetIsInstalling(true));
+
+                        if let Err(error) = GameManager::backup_prefix(output_file).await {
+                            let _ = sender.output(pages::settings::SettingsPageMsg::ShowError(
+                                format!("Failed to backup the wine prefix : {}", error),
+                            ));
+                        }
+
+                        let _ =
+                            sender.output(pages::settings::SettingsPageMsg::SetIsInstalling(false));
+                    });
+            }
+            HandleSettingsTaskMsg::RestorePrefix(backup_file) => {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .unwrap()
+                    .block_on(async {
+                        let _ =
+                            sender.output(pages::settings::SettingsPageMsg::SetIsInstalling(true));
+
+                        if let Err(error) =
+                            GameManager::restore_prefix(backup_file, None::<Arc<Noop>>).await
+                        {
+                            let _ = sender.output(pages::settings::SettingsPageMsg::ShowError(
+                                format!("Failed to restore the wine prefix : {}", error),
+                            ));
+                        }
+
+                        let _ =
+                            sender.output(pages::settings::SettingsPageMsg::SetIsInstalling(false));
+                    });
+            }
+            HandleSettingsTaskMsg::ResetPrefix => {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .unwrap()
+                    .block_on(async {
+                        let _ =
+                            sender.output(pages::settings::SettingsPageMsg::SetIsInstalling(true));
+
+                        // the prefix is created again by the reset
+                        let wine = get_wine().await;
+
+                        if let Err(error) =
+                            GameManager::reset_prefix(&wine, None::<Arc<Noop>>).await
+                        {
+                            let _ = sender.output(pages::settings::SettingsPageMsg::ShowError(
+                                format!("Failed to reset the wine prefix : {}", error),
+                            ));
+                        }
+
+                        let _ =
+                            sender.output(pages::settings::SettingsPageMsg::SetIsInstalling(false));
+                        let _ = sender.output(pages::settings::SettingsPageMsg::UpdateConfig);
+                    });
+            }
+            HandleSettingsTaskMsg::ApplyPrefixSettings(settings) => {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .unwrap()
+                    .block_on(async {
+                        let _ =
+                            sender.output(pages::settings::SettingsPageMsg::SetIsInstalling(true));
+
+                        // without a prefix, the settings are applied once it's created
+                        let wine = Some(get_wine().await)
+                            .filter(|wine| WineComponent::is_prefix_initialized(&wine.prefix));
+
+                        if let Err(error) =
+                            GameManager::set_prefix_settings(wine.as_ref(), settings).await
+                        {
+                            let _ = sender.output(pages::settings::SettingsPageMsg::ShowError(
+                                format!("Failed to apply the prefix settings : {}", error),
+                            ));
+                        }
+
+                        let _ =
+                            sender.output(pages::settings::SettingsPageMsg::SetIsInstalling(false));
+                    });
+            }
+        }
+    }
+}
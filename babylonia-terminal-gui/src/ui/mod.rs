@@ -40,6 +40,7 @@ struct MainWindow {
     setup_page: AsyncController<pages::steps::SetupPage>,
     game_page: AsyncController<pages::game::GamePage>,
     settings_page: AsyncConnector<pages::settings::SettingsPage>,
+    logs_page: AsyncConnector<pages::logs::LogsPage>,
     about_page: AsyncConnector<pages::about::AboutPage>,
     current_page: Pages,
     is_menu_visible: bool,
@@ -59,11 +60,14 @@ impl MainWindow {
 
         let settings_page = pages::settings::SettingsPage::builder().launch(());
 
+        let logs_page = pages::logs::LogsPage::builder().launch(());
+
         MainWindow {
             game_state,
             setup_page,
             game_page,
             settings_page,
+            logs_page,
             about_page,
             current_page: Pages::GamePage,
             is_menu_visible: false,
@@ -75,6 +79,7 @@ impl MainWindow {
 enum Pages {
     GamePage,
     SettingsPage,
+    LogsPage,
     AboutPage,
 }
 
@@ -145,6 +150,16 @@ impl SimpleAsyncComponent for MainWindow {
                                 model.settings_page.widget(),
                             },
 
+                            gtk::Box {
+                                set_orientation: gtk::Orientation::Vertical,
+                                set_vexpand: true,
+
+                                #[watch]
+                                set_visible: model.current_page == Pages::LogsPage,
+
+                                model.logs_page.widget(),
+                            },
+
                             gtk::Box {
                                 set_orientation: gtk::Orientation::Vertical,
                                 set_vexpand: true,
@@ -202,6 +217,13 @@ impl SimpleAsyncComponent for MainWindow {
                             connect_clicked => MainWindowMsg::SelectPage(Pages::SettingsPage),
                         },
 
+                        gtk::Button {
+                            set_margin_vertical: 5,
+                            set_label: "Logs",
+
+                            connect_clicked => MainWindowMsg::SelectPage(Pages::LogsPage),
+                        },
+
                         gtk::Button {
                             set_margin_vertical: 5,
                             set_label: "About",
@@ -265,6 +287,13 @@ impl SimpleAsyncComponent for MainWindow {
         match message {
             MainWindowMsg::ToggleMenuVisibility => self.is_menu_visible = !self.is_menu_visible,
             MainWindowMsg::SelectPage(page) => {
+                // the game may have been started since the last time
+                if page == Pages::LogsPage {
+                    self.logs_page
+                        .sender()
+                        .emit(pages::logs::LogsPageMsg::Refresh);
+                }
+
                 self.current_page = page;
                 self.is_menu_visible = false;
             }
@@ -1,17 +1,29 @@
-use libadwaita::prelude::{PreferencesPageExt, PreferencesRowExt};
+use std::path::PathBuf;
+
+use babylonia_terminal_sdk::{game_config::GameConfig, game_manager::GameManager};
+use libadwaita::prelude::{MessageDialogExt, PreferencesPageExt, PreferencesRowExt};
+use log::error;
 use relm4::{
-    gtk::prelude::{OrientableExt, WidgetExt},
+    gtk::prelude::{ButtonExt, GtkWindowExt, OrientableExt, WidgetExt},
     prelude::{adw, gtk, AsyncComponentParts, SimpleAsyncComponent},
     RelmWidgetExt,
 };
 
-use crate::APP_RESOURCE_PATH;
+use crate::{manager, ui::MAIN_WINDOW, APP_RESOURCE_PATH};
+
+#[derive(Debug)]
+pub enum AboutPageMsg {
+    ExportDiagnostics,
+    WriteDiagnostics(PathBuf),
+}
 
-pub struct AboutPage;
+pub struct AboutPage {
+    is_exporting: bool,
+}
 
 #[relm4::component(pub, async)]
 impl SimpleAsyncComponent for AboutPage {
-    type Input = ();
+    type Input = AboutPageMsg;
 
     type Output = ();
 
@@ -35,6 +47,18 @@ impl SimpleAsyncComponent for AboutPage {
                         add_css_class: "title-3",
                     },
 
+                    gtk::Button {
+                        set_halign: gtk::Align::Center,
+                        set_margin_top: 24,
+
+                        #[watch]
+                        set_label: if model.is_exporting { "Exporting..." } else { "Export diagnostics for a bug report" },
+                        #[watch]
+                        set_sensitive: !model.is_exporting,
+
+                        connect_clicked => AboutPageMsg::ExportDiagnostics,
+                    },
+
                     gtk::Label {
                         set_label: "This software was made under the GPL-3.0 license",
                         set_margin_vertical: 24,
@@ -728,9 +752,57 @@ Public License instead of this License.  But first, please read
         root: Self::Root,
         sender: relm4::AsyncComponentSender<Self>,
     ) -> relm4::prelude::AsyncComponentParts<Self> {
-        let model = AboutPage {};
+        let model = AboutPage {
+            is_exporting: false,
+        };
         let widgets = view_output!();
 
         AsyncComponentParts { model, widgets }
     }
+
+    async fn update(&mut self, message: Self::Input, sender: relm4::AsyncComponentSender<Self>) {
+        match message {
+            AboutPageMsg::ExportDiagnostics => {
+                let result = rfd::AsyncFileDialog::new()
+                    .set_directory(GameConfig::get_config_directory().await)
+                    .set_file_name("babylonia-terminal-report.tar.gz")
+                    .save_file()
+                    .await;
+
+                if let Some(result) = result {
+                    // the files of the game are checked, it can take a while
+                    self.is_exporting = true;
+                    sender.input(AboutPageMsg::WriteDiagnostics(result.path().to_path_buf()));
+                }
+            }
+            AboutPageMsg::WriteDiagnostics(output) => {
+                let wine = GameConfig::get_config()
+                    .await
+                    .is_wine_installed
+                    .then_some(manager::get_wine().await);
+                let message =
+                    match GameManager::export_diagnostics(wine.as_ref(), output.clone()).await {
+                        Ok(_) => format!(
+                            "The report was written to {}, you can attach it to your issue",
+                            output.display()
+                        ),
+                        Err(e) => {
+                            error!("Failed to export the diagnostics : {}", e);
+                            format!("Failed to export the diagnostics : {}", e)
+                        }
+                    };
+                self.is_exporting = false;
+
+                let dialog = unsafe {
+                    adw::MessageDialog::new(
+                        MAIN_WINDOW.as_ref(),
+                        Some("Diagnostics"),
+                        Some(&message),
+                    )
+                };
+                dialog.add_response("close", "Close");
+                dialog.present();
+            }
+        }
+    }
 }
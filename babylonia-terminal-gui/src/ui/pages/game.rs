@@ -1,7 +1,13 @@
 use std::{convert::identity, fmt::format, process::Command};
 
 use arboard::Clipboard;
-use babylonia_terminal_sdk::{game_config::GameConfig, game_state::GameState, utils};
+use babylonia_terminal_sdk::{
+    game_config::GameConfig,
+    game_manager::GameManager,
+    game_state::GameState,
+    playtime::{format_playtime, PlaytimeHistory},
+    utils,
+};
 use libadwaita::prelude::{ApplicationExt, MessageDialogExt, PreferencesPageExt};
 use log::{debug, error};
 use relm4::{
@@ -26,6 +32,8 @@ pub struct GamePage {
     game_handler: WorkerController<manager::HandleGameProcess>,
     installation_handler: WorkerController<manager::HandleGameInstallation>,
     is_game_running: bool,
+    is_stopping: bool,
+    playtime: String,
     is_downloading: bool,
     is_patching: bool,
     progress_bar_reporter: std::sync::Arc<ProgressBarGameInstallationReporter>,
@@ -34,9 +42,31 @@ pub struct GamePage {
     delete_old_setup_manager: DeleteOldSetupManager,
 }
 
+// empty if the game was never started or if the history can't be read
+async fn get_playtime_summary() -> String {
+    let history = match PlaytimeHistory::load().await {
+        Ok(history) => history,
+        Err(e) => {
+            error!("Failed to read the playtime : {}", e);
+            return String::new();
+        }
+    };
+
+    match history.get_last_session() {
+        Some(last_session) => format!(
+            "Last played {} · Total playtime {}",
+            last_session.started_at.format("%Y-%m-%d"),
+            format_playtime(history.get_total_playtime())
+        ),
+        None => String::new(),
+    }
+}
+
 #[derive(Debug)]
 pub enum GamePageMsg {
     SetIsGameRunning(bool),
+    SetIsStopping(bool),
+    StopGame,
     SetIsDownloading(bool),
     SetIsPatching(bool),
     UpdateGameState,
@@ -126,6 +156,33 @@ impl SimpleAsyncComponent for GamePage {
                         },
                     },
 
+                    gtk::Button {
+                        set_css_classes: &["destructive-action", "pill"],
+
+                        set_label: "Stop game",
+                        set_hexpand: false,
+                        set_width_request: 200,
+                        set_margin_top: 12,
+
+                        #[watch]
+                        set_visible: model.game_state == GameState::GameInstalled && model.is_game_running,
+
+                        #[watch]
+                        set_sensitive: !model.is_stopping,
+                        connect_clicked => GamePageMsg::StopGame,
+                    },
+
+                    gtk::Label {
+                        set_margin_top: 12,
+                        add_css_class: "dim-label",
+
+                        #[watch]
+                        set_label: &model.playtime,
+
+                        #[watch]
+                        set_visible: model.game_state == GameState::GameInstalled && !model.playtime.is_empty(),
+                    },
+
                     gtk::Button {
                         set_css_classes: &["suggested-action", "pill"],
 
@@ -233,6 +290,8 @@ impl SimpleAsyncComponent for GamePage {
                 .detach_worker(())
                 .forward(sender.input_sender(), identity),
             is_game_running: false,
+            is_stopping: false,
+            playtime: get_playtime_summary().await,
             is_downloading: false,
             is_patching: false,
             fraction: 0.0,
@@ -254,7 +313,29 @@ impl SimpleAsyncComponent for GamePage {
         sender: relm4::AsyncComponentSender<Self>,
     ) -> () {
         match message {
-            GamePageMsg::SetIsGameRunning(value) => self.is_game_running = value,
+            GamePageMsg::SetIsGameRunning(value) => {
+                self.is_game_running = value;
+                self.is_stopping = false;
+
+                if !value {
+                    self.playtime = get_playtime_summary().await;
+                }
+            }
+            GamePageMsg::SetIsStopping(value) => self.is_stopping = value,
+            GamePageMsg::StopGame => {
+                // the game can take a few seconds to exit, the page stays usable meanwhile
+                self.is_stopping = true;
+                let sender = sender.clone();
+                relm4::spawn(async move {
+                    if let Err(e) = GameManager::stop_game().await {
+                        sender.input(GamePageMsg::SetIsStopping(false));
+                        sender.input(GamePageMsg::ShowError(format!(
+                            "Unable to stop the game : {}",
+                            e
+                        )));
+                    }
+                });
+            }
             GamePageMsg::SetIsDownloading(value) => self.is_downloading = value,
             GamePageMsg::SetIsPatching(value) => self.is_patching = value,
             GamePageMsg::UpdateGameState => {
@@ -0,0 +1,196 @@
+use std::{convert::identity, path::PathBuf, process::Command};
+
+use babylonia_terminal_sdk::{
+    game_config::{GameConfig, LogSettings},
+    game_logs::LogSession,
+};
+use libadwaita::prelude::{
+    ActionRowExt, PreferencesGroupExt, PreferencesPageExt, PreferencesRowExt,
+};
+use log::error;
+use relm4::{
+    factory::{DynamicIndex, FactoryComponent, FactorySender, FactoryVecDeque},
+    gtk::prelude::{ButtonExt, OrientableExt, WidgetExt},
+    prelude::{adw, gtk, AsyncComponentParts, SimpleAsyncComponent},
+};
+
+#[derive(Debug)]
+pub enum LogsPageMsg {
+    Refresh,
+    SetRetention(usize),
+    SetCollectUnityLog(bool),
+    OpenSession(PathBuf),
+}
+
+pub struct LogsPage {
+    settings: LogSettings,
+    sessions: FactoryVecDeque<SessionRow>,
+}
+
+#[relm4::component(pub, async)]
+impl SimpleAsyncComponent for LogsPage {
+    type Input = LogsPageMsg;
+
+    type Output = ();
+
+    type Init = ();
+
+    view! {
+        gtk::Box {
+            set_orientation: gtk::Orientation::Vertical,
+
+            adw::PreferencesPage {
+                set_title: "Logs",
+
+                add = &adw::PreferencesGroup {
+                    set_width_request: 500,
+                    set_title: "Settings",
+
+                    adw::SpinRow {
+                        set_title: "Launches kept",
+                        set_subtitle: "The logs of the oldest launches are removed",
+                        set_adjustment: Some(&gtk::Adjustment::new(model.settings.retention as f64, 1.0, 100.0, 1.0, 10.0, 0.0)),
+
+                        connect_value_notify[sender] => move |row| {
+                            sender.input(LogsPageMsg::SetRetention(row.value() as usize))
+                        }
+                    },
+
+                    adw::SwitchRow {
+                        set_title: "Unity log",
+                        set_subtitle: "Keep the Player.log written by the game",
+                        set_active: model.settings.collect_unity_log,
+
+                        connect_active_notify[sender] => move |row| {
+                            sender.input(LogsPageMsg::SetCollectUnityLog(row.is_active()))
+                        }
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    set_width_request: 500,
+                    set_title: "Previous launches",
+
+                    #[local_ref]
+                    session_list -> gtk::ListBox {
+                        set_selection_mode: gtk::SelectionMode::None,
+                        add_css_class: "boxed-list",
+
+                        #[watch]
+                        set_visible: !model.sessions.is_empty(),
+                    },
+
+                    gtk::Label {
+                        set_label: "The game was not started yet",
+                        add_css_class: "dim-label",
+
+                        #[watch]
+                        set_visible: model.sessions.is_empty(),
+                    },
+                },
+            }
+        }
+    }
+
+    async fn init(
+        _init: Self::Init,
+        root: Self::Root,
+        sender: relm4::AsyncComponentSender<Self>,
+    ) -> AsyncComponentParts<Self> {
+        let mut model = LogsPage {
+            settings: GameConfig::get_log_settings().await,
+            sessions: FactoryVecDeque::builder()
+                .launch(gtk::ListBox::default())
+                .forward(sender.input_sender(), identity),
+        };
+        model.update_sessions().await;
+
+        let session_list = model.sessions.widget();
+        let widgets = view_output!();
+
+        AsyncComponentParts { model, widgets }
+    }
+
+    async fn update(&mut self, message: Self::Input, _sender: relm4::AsyncComponentSender<Self>) {
+        match message {
+            LogsPageMsg::Refresh => self.update_sessions().await,
+            LogsPageMsg::SetRetention(retention) => {
+                self.settings.retention = retention;
+                self.save_settings().await;
+            }
+            LogsPageMsg::SetCollectUnityLog(collect) => {
+                self.settings.collect_unity_log = collect;
+                self.save_settings().await;
+            }
+            LogsPageMsg::OpenSession(path) => {
+                if let Err(e) = Command::new("xdg-open").arg(path).spawn() {
+                    error!("Failed to open the logs : {}", e);
+                }
+            }
+        }
+    }
+}
+
+impl LogsPage {
+    async fn update_sessions(&mut self) {
+        let sessions = LogSession::list().await.unwrap_or_else(|e| {
+            error!("Failed to read the logs : {}", e);
+            vec![]
+        });
+
+        let mut guard = self.sessions.guard();
+        guard.clear();
+        for session in sessions {
+            guard.push_back(session);
+        }
+    }
+
+    async fn save_settings(&self) {
+        if let Err(e) = GameConfig::set_log_settings(self.settings.clone()).await {
+            error!("Failed to save the log settings : {}", e);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SessionRow {
+    session: LogSession,
+}
+
+#[relm4::factory]
+impl FactoryComponent for SessionRow {
+    type Init = LogSession;
+    type Input = ();
+    type Output = LogsPageMsg;
+    type CommandOutput = ();
+    type ParentWidget = gtk::ListBox;
+
+    view! {
+        adw::ActionRow {
+            set_title: &self.session.info.started_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            set_subtitle: &match (self.session.info.ended_at, self.session.info.exit_code) {
+                (None, _) => "Still running or interrupted".to_string(),
+                (Some(_), Some(code)) => format!("Exit code {}", code),
+                (Some(_), None) => "Killed".to_string(),
+            },
+
+            add_suffix = &gtk::Button {
+                set_icon_name: "folder-open-symbolic",
+                set_valign: gtk::Align::Center,
+                add_css_class: "flat",
+
+                connect_clicked[sender, path = self.session.path.clone()] => move |_| {
+                    let _ = sender.output(LogsPageMsg::OpenSession(path.clone()));
+                }
+            }
+        }
+    }
+
+    fn init_model(
+        session: Self::Init,
+        _index: &DynamicIndex,
+        _sender: FactorySender<Self>,
+    ) -> Self {
+        Self { session }
+    }
+}
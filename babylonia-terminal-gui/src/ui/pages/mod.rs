@@ -1,4 +1,5 @@
 pub mod about;
 pub mod game;
+pub mod logs;
 pub mod settings;
 pub mod steps;
@@ -1,31 +1,107 @@
+use std::{convert::identity, path::PathBuf, str::FromStr};
+
 use arboard::Clipboard;
-use babylonia_terminal_sdk::game_config::GameConfig;
+use babylonia_terminal_sdk::{
+    components::{
+        vkd3d_component::{Vkd3dComponent, VKD3D_DEV, VKD3D_REPO},
+        wine_component::WineComponent,
+    },
+    desktop_entry,
+    dxvk_config::DxvkSettings,
+    game_config::{GameConfig, RenderApi},
+    game_manager::{EnvironmentVariable, GameManager},
+    hooks::LaunchHooks,
+    launch_options::LaunchOptions,
+    prefix_manifest::{ManifestEntry, PrefixManifest},
+    prefix_settings::{check_dpi, PrefixSettings, VirtualDesktop, WindowsVersion},
+    runner::Runner,
+    runner_settings::{GraphicsDriver, RunnerSettings, SyncMode},
+    unity_player::{Resolution, UnityPlayerSettings, WindowMode},
+    utils::github_requester::{GithubRelease, GithubRequester},
+};
 use log::error;
 use relm4::{
+    factory::{DynamicIndex, FactoryComponent, FactorySender, FactoryVecDeque},
     gtk::{
-        prelude::{EditableExt, GtkWindowExt, OrientableExt, WidgetExt},
+        prelude::{ButtonExt, EditableExt, GtkWindowExt, OrientableExt, WidgetExt},
         InputHints,
     },
     prelude::{gtk, AsyncComponentParts, SimpleAsyncComponent},
+    Component, WorkerController,
 };
 
 use libadwaita::{
     self as adw,
     prelude::{
-        EntryRowExt, MessageDialogExt, PreferencesGroupExt, PreferencesPageExt, PreferencesRowExt,
+        ActionRowExt, ComboRowExt, EntryRowExt, MessageDialogExt, PreferencesGroupExt,
+        PreferencesPageExt, PreferencesRowExt,
     },
 };
 
-use crate::ui::MAIN_WINDOW;
+use crate::{manager, ui::MAIN_WINDOW};
 
 #[derive(Debug)]
 pub enum SettingsPageMsg {
     UpdateLaunchOption(Option<String>),
+    SetGamemode(bool),
+    SetMangohud(bool),
+    SetGamescope(bool),
+    UpdateGamescopeArgs(String),
+    UpdateGameArgs(String),
+    UpdateWindowMode(u32),
+    UpdateResolution(String),
+    UpdateMonitor(String),
+    SetPopupWindow(bool),
+    ResetGraphicsSettings,
+    CopyLaunchCommand,
+    SetDesktopEntry(bool),
+    SetEnvVar(String),
+    UnsetEnvVar(String),
+    UpdatePreLaunchHook(String),
+    UpdatePostExitHook(String),
+    UpdateRunnerKind(u32),
+    UpdateRunnerPath(String),
+    UpdateSync(u32),
+    UpdateGraphicsDriver(u32),
+    UpdateRenderApi(u32),
+    SelectVkd3dVersion(u32),
+    InstallVkd3d,
+    UpdateDxvkHud(String),
+    UpdateFrameLimit(String),
+    UpdateMaxFrameLatency(String),
+    ClearShaderCache,
+    AddVerb(String),
+    AddFont(String),
+    UpdateWindowsVersion(u32),
+    UpdateDpi(String),
+    UpdateVirtualDesktop(String),
+    UpdateDllOverrides(String),
+    BackupPrefix,
+    RestorePrefix,
+    ResetPrefix,
+    SetIsInstalling(bool),
+    UpdateConfig,
     ShowError(String),
 }
 
 pub struct SettingsPage {
-    launch_option: String,
+    launch_options: LaunchOptions,
+    is_desktop_entry_installed: bool,
+    unity: UnityPlayerSettings,
+    runner_kind: u32,
+    runner_path: String,
+    runner_settings: RunnerSettings,
+    render_api: RenderApi,
+    vkd3d_versions: Vec<GithubRelease>,
+    selected_vkd3d_version: usize,
+    is_vkd3d_installed: bool,
+    dxvk: DxvkSettings,
+    prefix_manifest: PrefixManifest,
+    prefix_settings: PrefixSettings,
+    is_installing: bool,
+    env_vars: FactoryVecDeque<EnvVarRow>,
+    hooks: LaunchHooks,
+    settings_task: WorkerController<manager::HandleSettingsTask>,
 }
 
 #[relm4::component(pub, async)]
@@ -48,9 +124,62 @@ impl SimpleAsyncComponent for SettingsPage {
                     set_title: "Launch option",
                     set_description: Some("Pass launch options to tinker the behavior of the game"),
 
+                    adw::SwitchRow {
+                        set_title: "GameMode",
+                        set_subtitle: "Start the game with gamemoderun",
+                        set_active: model.launch_options.gamemode,
+
+                        connect_active_notify[sender] => move |row| {
+                            sender.input(SettingsPageMsg::SetGamemode(row.is_active()))
+                        }
+                    },
+
+                    adw::SwitchRow {
+                        set_title: "MangoHud",
+                        set_subtitle: "Show the performance overlay",
+                        set_active: model.launch_options.mangohud,
+
+                        connect_active_notify[sender] => move |row| {
+                            sender.input(SettingsPageMsg::SetMangohud(row.is_active()))
+                        }
+                    },
+
+                    adw::SwitchRow {
+                        set_title: "Gamescope",
+                        set_subtitle: "Start the game inside gamescope",
+                        set_active: model.launch_options.gamescope.is_some(),
+
+                        connect_active_notify[sender] => move |row| {
+                            sender.input(SettingsPageMsg::SetGamescope(row.is_active()))
+                        }
+                    },
+
+                    adw::EntryRow {
+                        set_title: "Gamescope arguments, like -W 1920 -H 1080 -f",
+                        set_text: &LaunchOptions::format_args(model.launch_options.gamescope.as_deref().unwrap_or_default()),
+                        set_show_apply_button: true,
+
+                        #[watch]
+                        set_visible: model.launch_options.gamescope.is_some(),
+
+                        connect_apply[sender] => move |entry| {
+                            sender.input(SettingsPageMsg::UpdateGamescopeArgs(entry.text().trim().to_string()))
+                        }
+                    },
+
+                    adw::EntryRow {
+                        set_title: "Game arguments",
+                        set_text: &LaunchOptions::format_args(&model.launch_options.game_args),
+                        set_show_apply_button: true,
+
+                        connect_apply[sender] => move |entry| {
+                            sender.input(SettingsPageMsg::UpdateGameArgs(entry.text().trim().to_string()))
+                        }
+                    },
+
                     adw::EntryRow {
                         set_title: "%command%",
-                        set_text: &model.launch_option,
+                        set_text: model.launch_options.raw.as_deref().unwrap_or_default(),
 
                         connect_changed[sender] => move |entry| {
                             let command = entry.text().trim().to_string();
@@ -60,7 +189,453 @@ impl SimpleAsyncComponent for SettingsPage {
                                 sender.input(SettingsPageMsg::UpdateLaunchOption(Some(command)))
                             }
                         }
-                    }
+                    },
+
+                    adw::SwitchRow {
+                        set_title: "Add to application menu",
+                        set_subtitle: "Start the game directly from the menu of your desktop",
+                        set_active: model.is_desktop_entry_installed,
+
+                        connect_active_notify[sender] => move |row| {
+                            sender.input(SettingsPageMsg::SetDesktopEntry(row.is_active()))
+                        }
+                    },
+
+                    adw::ActionRow {
+                        set_title: "Launch command",
+                        set_subtitle: "Copy the command used to start the game, to run it from a terminal",
+
+                        add_suffix = &gtk::Button {
+                            set_valign: gtk::Align::Center,
+                            set_label: "Copy",
+
+                            connect_clicked => SettingsPageMsg::CopyLaunchCommand,
+                        },
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    set_width_request: 500,
+                    set_title: "Game window",
+                    set_description: Some("Passed to the game as Unity arguments, the empty ones let the game choose"),
+
+                    adw::ComboRow {
+                        set_title: "Window mode",
+                        set_model: Some(&gtk::StringList::new(&["Default", "Fullscreen", "Borderless", "Windowed"])),
+                        set_selected: model
+                            .unity
+                            .window_mode
+                            .and_then(|m| WindowMode::all().iter().position(|a| *a == m))
+                            .map(|i| i as u32 + 1)
+                            .unwrap_or(0),
+
+                        connect_selected_notify[sender] => move |row| {
+                            sender.input(SettingsPageMsg::UpdateWindowMode(row.selected()))
+                        }
+                    },
+
+                    adw::EntryRow {
+                        set_title: "Resolution, like 1920x1080",
+                        set_text: &model.unity.resolution.map(|r| r.to_string()).unwrap_or_default(),
+                        set_show_apply_button: true,
+
+                        connect_apply[sender] => move |entry| {
+                            sender.input(SettingsPageMsg::UpdateResolution(entry.text().trim().to_string()))
+                        }
+                    },
+
+                    adw::EntryRow {
+                        set_title: "Monitor, starting from 1",
+                        set_text: &model.unity.monitor.map(|m| m.to_string()).unwrap_or_default(),
+                        set_input_purpose: gtk::InputPurpose::Digits,
+                        set_show_apply_button: true,
+
+                        connect_apply[sender] => move |entry| {
+                            sender.input(SettingsPageMsg::UpdateMonitor(entry.text().trim().to_string()))
+                        }
+                    },
+
+                    adw::SwitchRow {
+                        set_title: "Popup window",
+                        set_subtitle: "A window without decoration, only with the windowed mode",
+                        #[watch]
+                        set_active: model.unity.popup_window,
+                        #[watch]
+                        set_sensitive: !model.unity.window_mode.is_some_and(|m| m != WindowMode::Windowed),
+
+                        connect_active_notify[sender] => move |row| {
+                            sender.input(SettingsPageMsg::SetPopupWindow(row.is_active()))
+                        }
+                    },
+
+                    adw::ActionRow {
+                        set_title: "Reset graphics settings",
+                        set_subtitle: "Remove the resolution and the quality saved by the game, when it can't start anymore",
+
+                        add_suffix = &gtk::Button {
+                            set_valign: gtk::Align::Center,
+                            set_label: "Reset",
+                            add_css_class: "destructive-action",
+
+                            connect_clicked => SettingsPageMsg::ResetGraphicsSettings,
+                        },
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    set_width_request: 500,
+                    set_title: "Environment variables",
+                    set_description: Some("Passed to the game on each launch"),
+
+                    #[local_ref]
+                    env_var_list -> gtk::ListBox {
+                        set_selection_mode: gtk::SelectionMode::None,
+                        add_css_class: "boxed-list",
+
+                        #[watch]
+                        set_visible: !model.env_vars.is_empty(),
+                    },
+
+                    adw::EntryRow {
+                        set_title: "Add a variable, like DXVK_HUD=fps",
+                        set_show_apply_button: true,
+                        set_margin_top: 12,
+                        add_css_class: "card",
+
+                        connect_apply[sender] => move |entry| {
+                            sender.input(SettingsPageMsg::SetEnvVar(entry.text().trim().to_string()));
+                            entry.set_text("");
+                        }
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    set_width_request: 500,
+                    set_title: "Hooks",
+                    set_description: Some("Shell commands run around the game, they get the BT_GAME_DIR, BT_PREFIX, BT_LOG_DIR and BT_EXIT_CODE variables"),
+
+                    adw::EntryRow {
+                        set_title: "Before the launch, the game is not started if it fails",
+                        set_text: model.hooks.pre_launch.as_deref().unwrap_or_default(),
+                        set_show_apply_button: true,
+
+                        connect_apply[sender] => move |entry| {
+                            sender.input(SettingsPageMsg::UpdatePreLaunchHook(entry.text().trim().to_string()))
+                        }
+                    },
+
+                    adw::EntryRow {
+                        set_title: "After the game exits",
+                        set_text: model.hooks.post_exit.as_deref().unwrap_or_default(),
+                        set_show_apply_button: true,
+
+                        connect_apply[sender] => move |entry| {
+                            sender.input(SettingsPageMsg::UpdatePostExitHook(entry.text().trim().to_string()))
+                        }
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    set_width_request: 500,
+                    set_title: "Runner",
+                    set_description: Some("Proton and umu-launcher are using their own prefix"),
+
+                    adw::ComboRow {
+                        set_title: "Start the game with",
+                        set_model: Some(&gtk::StringList::new(&["Bundled wine", "System wine", "Proton", "umu-launcher", "Custom command"])),
+                        set_selected: model.runner_kind,
+
+                        connect_selected_notify[sender] => move |row| {
+                            sender.input(SettingsPageMsg::UpdateRunnerKind(row.selected()))
+                        }
+                    },
+
+                    adw::EntryRow {
+                        #[watch]
+                        set_title: match model.runner_kind {
+                            2 => "Proton build directory",
+                            3 => "Proton build directory (optional)",
+                            _ => "Command, the game is added after its arguments",
+                        },
+                        set_text: &model.runner_path,
+
+                        #[watch]
+                        set_visible: model.runner_kind >= 2,
+
+                        connect_changed[sender] => move |entry| {
+                            sender.input(SettingsPageMsg::UpdateRunnerPath(entry.text().trim().to_string()))
+                        }
+                    },
+
+                    adw::ComboRow {
+                        set_title: "Synchronization",
+                        set_subtitle: "NTSync needs a recent kernel and wine",
+                        set_model: Some(&gtk::StringList::new(&["Default", "Wineserver", "Esync", "Fsync", "NTSync"])),
+                        // reset when the selection is not supported
+                        #[watch]
+                        set_selected: model
+                            .runner_settings
+                            .sync
+                            .and_then(|s| SyncMode::all().iter().position(|a| *a == s))
+                            .map(|i| i as u32 + 1)
+                            .unwrap_or(0),
+
+                        connect_selected_notify[sender] => move |row| {
+                            sender.input(SettingsPageMsg::UpdateSync(row.selected()))
+                        }
+                    },
+
+                    adw::ComboRow {
+                        set_title: "Graphics driver",
+                        set_model: Some(&gtk::StringList::new(&["Default", "X11", "Wayland"])),
+                        #[watch]
+                        set_selected: model
+                            .runner_settings
+                            .graphics_driver
+                            .and_then(|d| GraphicsDriver::all().iter().position(|a| *a == d))
+                            .map(|i| i as u32 + 1)
+                            .unwrap_or(0),
+
+                        connect_selected_notify[sender] => move |row| {
+                            sender.input(SettingsPageMsg::UpdateGraphicsDriver(row.selected()))
+                        }
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    set_width_request: 500,
+                    set_title: "Rendering",
+                    set_description: Some("DirectX 12 needs VKD3D-Proton to be installed in the wine prefix"),
+
+                    adw::ComboRow {
+                        set_title: "Render API",
+                        set_model: Some(&gtk::StringList::new(&["DirectX 11 (DXVK)", "DirectX 12 (VKD3D-Proton)"])),
+                        set_selected: if model.render_api == RenderApi::D3D12 { 1 } else { 0 },
+
+                        connect_selected_notify[sender] => move |row| {
+                            sender.input(SettingsPageMsg::UpdateRenderApi(row.selected()))
+                        }
+                    },
+
+                    adw::ComboRow {
+                        set_title: "VKD3D-Proton version",
+                        set_model: Some(&gtk::StringList::new(model
+                            .vkd3d_versions
+                            .iter()
+                            .map(|r| r.tag_name.as_str())
+                            .collect::<Vec<&str>>()
+                            .as_slice())),
+
+                        connect_selected_notify[sender] => move |row| {
+                            sender.input(SettingsPageMsg::SelectVkd3dVersion(row.selected()))
+                        }
+                    },
+
+                    adw::ActionRow {
+                        set_title: "VKD3D-Proton",
+                        #[watch]
+                        set_subtitle: if model.is_vkd3d_installed { "Installed" } else { "Not installed" },
+
+                        add_suffix = &gtk::Spinner {
+                            set_spinning: true,
+
+                            #[watch]
+                            set_visible: model.is_installing,
+                        },
+
+                        add_suffix = &gtk::Button {
+                            set_valign: gtk::Align::Center,
+
+                            #[watch]
+                            set_label: if model.is_vkd3d_installed { "Reinstall" } else { "Install" },
+
+                            #[watch]
+                            set_sensitive: !model.is_installing && !model.vkd3d_versions.is_empty(),
+
+                            connect_clicked => SettingsPageMsg::InstallVkd3d,
+                        },
+                    },
+
+                    adw::EntryRow {
+                        set_title: "DXVK HUD, like fps,frametimes",
+                        set_text: model.dxvk.hud.as_deref().unwrap_or_default(),
+                        set_show_apply_button: true,
+
+                        connect_apply[sender] => move |entry| {
+                            sender.input(SettingsPageMsg::UpdateDxvkHud(entry.text().trim().to_string()))
+                        }
+                    },
+
+                    adw::EntryRow {
+                        set_title: "Frame limit (disabled if empty)",
+                        set_text: &model.dxvk.frame_limit.map(|l| l.to_string()).unwrap_or_default(),
+                        set_input_purpose: gtk::InputPurpose::Digits,
+                        set_show_apply_button: true,
+
+                        connect_apply[sender] => move |entry| {
+                            sender.input(SettingsPageMsg::UpdateFrameLimit(entry.text().trim().to_string()))
+                        }
+                    },
+
+                    adw::EntryRow {
+                        set_title: "Maximum frame latency, from 1 to 16",
+                        set_text: &model.dxvk.max_frame_latency.map(|l| l.to_string()).unwrap_or_default(),
+                        set_input_purpose: gtk::InputPurpose::Digits,
+                        set_show_apply_button: true,
+
+                        connect_apply[sender] => move |entry| {
+                            sender.input(SettingsPageMsg::UpdateMaxFrameLatency(entry.text().trim().to_string()))
+                        }
+                    },
+
+                    adw::ActionRow {
+                        set_title: "Shader cache",
+                        set_subtitle: "Remove the shader caches of DXVK and VKD3D-Proton, they are rebuilt while playing",
+
+                        add_suffix = &gtk::Button {
+                            set_valign: gtk::Align::Center,
+                            set_label: "Clear",
+
+                            connect_clicked => SettingsPageMsg::ClearShaderCache,
+                        },
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    set_width_request: 500,
+                    set_title: "Prefix settings",
+                    set_description: Some("What winecfg usually changes, applied to the wine prefix"),
+
+                    adw::ComboRow {
+                        set_title: "Windows version",
+                        set_model: Some(&gtk::StringList::new(&["Default", "Windows 7", "Windows 8", "Windows 8.1", "Windows 10", "Windows 11"])),
+                        set_selected: model
+                            .prefix_settings
+                            .windows_version
+                            .and_then(|v| WindowsVersion::all().iter().position(|a| *a == v))
+                            .map(|i| i as u32 + 1)
+                            .unwrap_or(0),
+
+                        connect_selected_notify[sender] => move |row| {
+                            sender.input(SettingsPageMsg::UpdateWindowsVersion(row.selected()))
+                        }
+                    },
+
+                    adw::EntryRow {
+                        set_title: "DPI (96 by default)",
+                        set_text: &model.prefix_settings.dpi.map(|d| d.to_string()).unwrap_or_default(),
+                        set_input_purpose: gtk::InputPurpose::Digits,
+                        set_show_apply_button: true,
+
+                        connect_apply[sender] => move |entry| {
+                            sender.input(SettingsPageMsg::UpdateDpi(entry.text().trim().to_string()))
+                        }
+                    },
+
+                    adw::EntryRow {
+                        set_title: "Virtual desktop size, like 1920x1080 (disabled if empty)",
+                        set_text: &model.prefix_settings.virtual_desktop.map(|d| d.to_string()).unwrap_or_default(),
+                        set_show_apply_button: true,
+
+                        connect_apply[sender] => move |entry| {
+                            sender.input(SettingsPageMsg::UpdateVirtualDesktop(entry.text().trim().to_string()))
+                        }
+                    },
+
+                    adw::EntryRow {
+                        set_title: "DLL overrides, like d3dcompiler_47=n;mscoree=",
+                        set_text: &model.prefix_settings.format_dll_overrides(),
+                        set_show_apply_button: true,
+
+                        connect_apply[sender] => move |entry| {
+                            sender.input(SettingsPageMsg::UpdateDllOverrides(entry.text().trim().to_string()))
+                        }
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    set_width_request: 500,
+                    set_title: "Wine prefix",
+                    set_description: Some("The winetricks verbs and fonts installed in the wine prefix, use cjkfonts for the Asian languages"),
+
+                    adw::ActionRow {
+                        set_title: "Dependencies",
+                        #[watch]
+                        set_subtitle: &format_entries(&model.prefix_manifest.verbs),
+                    },
+
+                    adw::ActionRow {
+                        set_title: "Fonts",
+                        #[watch]
+                        set_subtitle: &format_entries(&model.prefix_manifest.fonts),
+                    },
+
+                    adw::EntryRow {
+                        set_title: "Add a winetricks verb",
+                        set_show_apply_button: true,
+
+                        #[watch]
+                        set_sensitive: !model.is_installing,
+
+                        connect_apply[sender] => move |entry| {
+                            sender.input(SettingsPageMsg::AddVerb(entry.text().trim().to_string()));
+                            entry.set_text("");
+                        }
+                    },
+
+                    adw::EntryRow {
+                        set_title: "Add a font",
+                        set_show_apply_button: true,
+
+                        #[watch]
+                        set_sensitive: !model.is_installing,
+
+                        connect_apply[sender] => move |entry| {
+                            sender.input(SettingsPageMsg::AddFont(entry.text().trim().to_string()));
+                            entry.set_text("");
+                        }
+                    },
+
+                    adw::ActionRow {
+                        set_title: "Backup",
+                        set_subtitle: "Save the wine prefix to an archive, or replace it by a saved one",
+
+                        add_suffix = &gtk::Button {
+                            set_valign: gtk::Align::Center,
+                            set_label: "Save",
+
+                            #[watch]
+                            set_sensitive: !model.is_installing,
+
+                            connect_clicked => SettingsPageMsg::BackupPrefix,
+                        },
+
+                        add_suffix = &gtk::Button {
+                            set_valign: gtk::Align::Center,
+                            set_label: "Restore",
+
+                            #[watch]
+                            set_sensitive: !model.is_installing,
+
+                            connect_clicked => SettingsPageMsg::RestorePrefix,
+                        },
+                    },
+
+                    adw::ActionRow {
+                        set_title: "Reset",
+                        set_subtitle: "Recreate the wine prefix and reinstall DXVK, the fonts and the dependencies",
+
+                        add_suffix = &gtk::Button {
+                            set_valign: gtk::Align::Center,
+                            set_label: "Reset",
+                            add_css_class: "destructive-action",
+
+                            #[watch]
+                            set_sensitive: !model.is_installing,
+
+                            connect_clicked => SettingsPageMsg::ResetPrefix,
+                        },
+                    },
                 }
             }
         }
@@ -71,15 +646,57 @@ impl SimpleAsyncComponent for SettingsPage {
         root: Self::Root,
         sender: relm4::AsyncComponentSender<Self>,
     ) -> relm4::prelude::AsyncComponentParts<Self> {
-        let launch_option = match GameConfig::get_launch_options().await {
-            Err(e) => String::new(),
-            Ok(v) => match v {
-                None => String::new(),
-                Some(launch) => launch,
-            },
+        let vkd3d_versions = match Vkd3dComponent::get_github_releases(VKD3D_DEV, VKD3D_REPO).await
+        {
+            Ok(releases) => releases,
+            Err(e) => {
+                error!("Failed to fetch the VKD3D-Proton versions : {}", e);
+                vec![]
+            }
         };
 
-        let model = SettingsPage { launch_option };
+        let config = GameConfig::get_config().await;
+
+        let (runner_kind, runner_path) = match &config.runner {
+            Runner::Bundled => (0, String::new()),
+            Runner::System => (1, String::new()),
+            Runner::Proton { path } => (2, path.to_string_lossy().into_owned()),
+            Runner::Umu { proton_path } => (
+                3,
+                proton_path
+                    .as_ref()
+                    .map(|p| p.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+            ),
+            Runner::Custom { program, args } => (4, Runner::format_custom_command(program, args)),
+        };
+
+        let mut model = SettingsPage {
+            launch_options: config.launch_options.clone(),
+            is_desktop_entry_installed: desktop_entry::is_installed(),
+            unity: config.unity,
+            runner_kind,
+            runner_path,
+            runner_settings: config.runner_settings,
+            render_api: config.render_api,
+            vkd3d_versions,
+            selected_vkd3d_version: 0,
+            is_vkd3d_installed: config.is_vkd3d_installed,
+            dxvk: config.dxvk,
+            prefix_manifest: config.prefix_manifest,
+            prefix_settings: config.prefix_settings,
+            is_installing: false,
+            env_vars: FactoryVecDeque::builder()
+                .launch(gtk::ListBox::default())
+                .forward(sender.input_sender(), identity),
+            hooks: config.hooks,
+            settings_task: manager::HandleSettingsTask::builder()
+                .detach_worker(())
+                .forward(sender.input_sender(), identity),
+        };
+        model.update_env_vars(config.launch_options.env_vars.clone());
+
+        let env_var_list = model.env_vars.widget();
         let widgets = view_output!();
 
         AsyncComponentParts { model, widgets }
@@ -88,13 +705,448 @@ impl SimpleAsyncComponent for SettingsPage {
     async fn update(&mut self, message: Self::Input, sender: relm4::AsyncComponentSender<Self>) {
         match message {
             SettingsPageMsg::UpdateLaunchOption(new_launch_option) => {
-                if let Err(e) = GameConfig::set_launch_options(new_launch_option).await {
+                self.launch_options.raw = new_launch_option;
+                self.save_launch_options(&sender).await;
+            }
+            SettingsPageMsg::SetGamemode(value) => {
+                self.launch_options.gamemode = value;
+                self.save_launch_options(&sender).await;
+            }
+            SettingsPageMsg::SetMangohud(value) => {
+                self.launch_options.mangohud = value;
+                self.save_launch_options(&sender).await;
+            }
+            SettingsPageMsg::SetGamescope(value) => {
+                self.launch_options.gamescope = match (value, self.launch_options.gamescope.take())
+                {
+                    (true, args) => Some(args.unwrap_or_default()),
+                    (false, _) => None,
+                };
+                self.save_launch_options(&sender).await;
+            }
+            SettingsPageMsg::UpdateGamescopeArgs(args) => match LaunchOptions::parse_args(&args) {
+                Ok(args) => {
+                    self.launch_options.gamescope = Some(args);
+                    self.save_launch_options(&sender).await;
+                }
+                Err(e) => sender.input(SettingsPageMsg::ShowError(e.to_string())),
+            },
+            SettingsPageMsg::UpdateGameArgs(args) => match LaunchOptions::parse_args(&args) {
+                Ok(args) => {
+                    self.launch_options.game_args = args;
+                    self.save_launch_options(&sender).await;
+                }
+                Err(e) => sender.input(SettingsPageMsg::ShowError(e.to_string())),
+            },
+            SettingsPageMsg::SetEnvVar(variable) => {
+                let variable = match EnvironmentVariable::parse(&variable) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        sender.input(SettingsPageMsg::ShowError(e.to_string()));
+                        return;
+                    }
+                };
+
+                self.launch_options.set_env_var(variable);
+                self.update_env_vars(self.launch_options.env_vars.clone());
+                self.save_launch_options(&sender).await;
+            }
+            SettingsPageMsg::UnsetEnvVar(name) => {
+                self.launch_options.unset_env_var(&name);
+                self.update_env_vars(self.launch_options.env_vars.clone());
+                self.save_launch_options(&sender).await;
+            }
+            SettingsPageMsg::UpdatePreLaunchHook(command) => {
+                self.hooks.pre_launch = Some(command).filter(|c| !c.is_empty());
+                self.save_hooks(&sender).await;
+            }
+            SettingsPageMsg::UpdatePostExitHook(command) => {
+                self.hooks.post_exit = Some(command).filter(|c| !c.is_empty());
+                self.save_hooks(&sender).await;
+            }
+            SettingsPageMsg::UpdateRunnerKind(selected) => {
+                self.runner_kind = selected;
+                self.save_runner(&sender).await;
+            }
+            SettingsPageMsg::UpdateRunnerPath(path) => {
+                self.runner_path = path;
+                self.save_runner(&sender).await;
+            }
+            SettingsPageMsg::UpdateSync(selected) => {
+                let mut settings = self.runner_settings;
+                settings.sync = match selected {
+                    0 => None,
+                    i => SyncMode::all().get(i as usize - 1).copied(),
+                };
+                self.save_runner_settings(settings, &sender).await;
+            }
+            SettingsPageMsg::UpdateGraphicsDriver(selected) => {
+                let mut settings = self.runner_settings;
+                settings.graphics_driver = match selected {
+                    0 => None,
+                    i => GraphicsDriver::all().get(i as usize - 1).copied(),
+                };
+                self.save_runner_settings(settings, &sender).await;
+            }
+            SettingsPageMsg::UpdateRenderApi(selected) => {
+                self.render_api = if selected == 1 {
+                    RenderApi::D3D12
+                } else {
+                    RenderApi::D3D11
+                };
+
+                if let Err(e) = GameConfig::set_render_api(self.render_api).await {
                     sender.input(SettingsPageMsg::ShowError(format!(
-                        "Something went wrong when updated the launch options : {}",
+                        "Something went wrong when updated the render API : {}",
                         e
                     )));
                 }
             }
+            SettingsPageMsg::SelectVkd3dVersion(selected) => {
+                self.selected_vkd3d_version = selected as usize;
+            }
+            SettingsPageMsg::InstallVkd3d => {
+                let _ =
+                    self.settings_task
+                        .sender()
+                        .send(manager::HandleSettingsTaskMsg::InstallVkd3d(
+                            self.selected_vkd3d_version,
+                        ));
+            }
+            SettingsPageMsg::UpdateDxvkHud(hud) => {
+                let mut settings = self.dxvk.clone();
+                settings.hud = Some(hud).filter(|h| !h.is_empty());
+                self.save_dxvk_settings(settings, &sender).await;
+            }
+            SettingsPageMsg::UpdateFrameLimit(limit) => {
+                let mut settings = self.dxvk.clone();
+                settings.frame_limit = match parse_optional_number(&limit) {
+                    Ok(limit) => limit,
+                    Err(e) => {
+                        sender.input(SettingsPageMsg::ShowError(format!(
+                            "Invalid frame limit : {}",
+                            e
+                        )));
+                        return;
+                    }
+                };
+                self.save_dxvk_settings(settings, &sender).await;
+            }
+            SettingsPageMsg::UpdateMaxFrameLatency(latency) => {
+                let mut settings = self.dxvk.clone();
+                settings.max_frame_latency = match parse_optional_number(&latency) {
+                    Ok(latency) => latency,
+                    Err(e) => {
+                        sender.input(SettingsPageMsg::ShowError(format!(
+                            "Invalid frame latency : {}",
+                            e
+                        )));
+                        return;
+                    }
+                };
+                self.save_dxvk_settings(settings, &sender).await;
+            }
+            SettingsPageMsg::ClearShaderCache => {
+                let message = match GameManager::clear_shader_cache().await {
+                    Ok(0) => "The shader cache was already empty".to_string(),
+                    Ok(count) => format!("{} shader cache file(s) removed", count),
+                    Err(e) => {
+                        sender.input(SettingsPageMsg::ShowError(format!(
+                            "Failed to clear the shader cache : {}",
+                            e
+                        )));
+                        return;
+                    }
+                };
+
+                let dialog = unsafe {
+                    adw::MessageDialog::new(
+                        MAIN_WINDOW.as_ref(),
+                        Some("Shader cache"),
+                        Some(&message),
+                    )
+                };
+                dialog.add_response("close", "Close");
+                dialog.present();
+            }
+            SettingsPageMsg::AddVerb(verb) => match GameConfig::add_winetricks_verb(&verb).await {
+                Ok(true) => {
+                    let _ = self
+                        .settings_task
+                        .sender()
+                        .send(manager::HandleSettingsTaskMsg::InstallPrefixManifest);
+                }
+                Ok(false) => {}
+                Err(e) => sender.input(SettingsPageMsg::ShowError(format!(
+                    "Something went wrong when added the winetricks verb : {}",
+                    e
+                ))),
+            },
+            SettingsPageMsg::AddFont(font) => match GameConfig::add_font(&font).await {
+                Ok(true) => {
+                    let _ = self
+                        .settings_task
+                        .sender()
+                        .send(manager::HandleSettingsTaskMsg::InstallPrefixManifest);
+                }
+                Ok(false) => {}
+                Err(e) => sender.input(SettingsPageMsg::ShowError(format!(
+                    "Something went wrong when added the font : {}",
+                    e
+                ))),
+            },
+            SettingsPageMsg::UpdateWindowsVersion(selected) => {
+                let mut settings = self.prefix_settings.clone();
+                settings.windows_version = match selected {
+                    0 => None,
+                    i => WindowsVersion::all().get(i as usize - 1).copied(),
+                };
+                self.save_prefix_settings(settings);
+            }
+            SettingsPageMsg::UpdateDpi(dpi) => {
+                let mut settings = self.prefix_settings.clone();
+                settings.dpi = if dpi.is_empty() {
+                    None
+                } else {
+                    match dpi
+                        .parse::<u32>()
+                        .map_err(anyhow::Error::from)
+                        .and_then(|d| {
+                            check_dpi(d)?;
+                            Ok(d)
+                        }) {
+                        Ok(d) => Some(d),
+                        Err(e) => {
+                            sender
+                                .input(SettingsPageMsg::ShowError(format!("Invalid DPI : {}", e)));
+                            return;
+                        }
+                    }
+                };
+                self.save_prefix_settings(settings);
+            }
+            SettingsPageMsg::UpdateVirtualDesktop(size) => {
+                let mut settings = self.prefix_settings.clone();
+                settings.virtual_desktop = if size.is_empty() {
+                    None
+                } else {
+                    match VirtualDesktop::from_str(&size) {
+                        Ok(desktop) => Some(desktop),
+                        Err(e) => {
+                            sender.input(SettingsPageMsg::ShowError(e.to_string()));
+                            return;
+                        }
+                    }
+                };
+                self.save_prefix_settings(settings);
+            }
+            SettingsPageMsg::UpdateDllOverrides(overrides) => {
+                let mut settings = self.prefix_settings.clone();
+                settings.dll_overrides = match PrefixSettings::parse_dll_overrides(&overrides) {
+                    Ok(overrides) => overrides,
+                    Err(e) => {
+                        sender.input(SettingsPageMsg::ShowError(e.to_string()));
+                        return;
+                    }
+                };
+                self.save_prefix_settings(settings);
+            }
+            SettingsPageMsg::UpdateWindowMode(selected) => {
+                let mut settings = self.unity.clone();
+                settings.window_mode = match selected {
+                    0 => None,
+                    i => WindowMode::all().get(i as usize - 1).copied(),
+                };
+
+                // the popup window can't be used with the other modes
+                if settings
+                    .window_mode
+                    .is_some_and(|m| m != WindowMode::Windowed)
+                {
+                    settings.popup_window = false;
+                }
+
+                self.save_unity_settings(settings, &sender).await;
+            }
+            SettingsPageMsg::UpdateResolution(resolution) => {
+                let mut settings = self.unity.clone();
+                settings.resolution = if resolution.is_empty() {
+                    None
+                } else {
+                    match Resolution::from_str(&resolution) {
+                        Ok(resolution) => Some(resolution),
+                        Err(e) => {
+                            sender.input(SettingsPageMsg::ShowError(e.to_string()));
+                            return;
+                        }
+                    }
+                };
+                self.save_unity_settings(settings, &sender).await;
+            }
+            SettingsPageMsg::UpdateMonitor(monitor) => {
+                let mut settings = self.unity.clone();
+                settings.monitor = if monitor.is_empty() {
+                    None
+                } else {
+                    match monitor.parse::<u32>() {
+                        Ok(monitor) => Some(monitor),
+                        Err(e) => {
+                            sender.input(SettingsPageMsg::ShowError(format!(
+                                "Invalid monitor : {}",
+                                e
+                            )));
+                            return;
+                        }
+                    }
+                };
+                self.save_unity_settings(settings, &sender).await;
+            }
+            SettingsPageMsg::SetPopupWindow(value) => {
+                let mut settings = self.unity.clone();
+                settings.popup_window = value;
+                self.save_unity_settings(settings, &sender).await;
+            }
+            SettingsPageMsg::ResetGraphicsSettings => {
+                let Some(game_dir) = GameConfig::get_game_dir().await else {
+                    sender.input(SettingsPageMsg::ShowError(
+                        "The game directory was not found, the game needs to be installed first"
+                            .to_string(),
+                    ));
+                    return;
+                };
+
+                let message = match GameManager::reset_graphics_settings(game_dir).await {
+                    Ok(0) => "No graphics setting was saved by the game".to_string(),
+                    Ok(count) => format!(
+                        "{} graphics setting(s) removed, the game will use its default ones",
+                        count
+                    ),
+                    Err(e) => {
+                        sender.input(SettingsPageMsg::ShowError(format!(
+                            "Failed to reset the graphics settings : {}",
+                            e
+                        )));
+                        return;
+                    }
+                };
+
+                let dialog = unsafe {
+                    adw::MessageDialog::new(
+                        MAIN_WINDOW.as_ref(),
+                        Some("Graphics settings"),
+                        Some(&message),
+                    )
+                };
+                dialog.add_response("close", "Close");
+                dialog.present();
+            }
+            SettingsPageMsg::CopyLaunchCommand => {
+                let Some(game_dir) = GameConfig::get_game_dir().await else {
+                    sender.input(SettingsPageMsg::ShowError(
+                        "The game directory was not found, the game needs to be installed first"
+                            .to_string(),
+                    ));
+                    return;
+                };
+
+                let wine = manager::get_wine().await;
+                let prepared = GameManager::prepare_launch(&wine, game_dir, None, vec![]).await;
+
+                match prepared {
+                    Ok(prepared) => {
+                        if let Err(err) = Clipboard::new()
+                            .unwrap()
+                            .set_text(prepared.to_shell_command())
+                        {
+                            error!(
+                                "Failed to copy the launch command to the clipboard : {}",
+                                err
+                            );
+                        }
+                    }
+                    Err(e) => sender.input(SettingsPageMsg::ShowError(format!(
+                        "Failed to prepare the launch : {}",
+                        e
+                    ))),
+                }
+            }
+            SettingsPageMsg::SetDesktopEntry(value) => {
+                if value == self.is_desktop_entry_installed {
+                    return;
+                }
+
+                let result = if value {
+                    match std::env::current_exe() {
+                        Ok(executable) => desktop_entry::install(&executable).await,
+                        Err(e) => Err(e.into()),
+                    }
+                } else {
+                    desktop_entry::uninstall().await.map(|_| ())
+                };
+
+                match result {
+                    Ok(_) => self.is_desktop_entry_installed = value,
+                    Err(e) => sender.input(SettingsPageMsg::ShowError(format!(
+                        "Something went wrong when updated the application menu : {}",
+                        e
+                    ))),
+                }
+            }
+            SettingsPageMsg::BackupPrefix => {
+                let result = rfd::AsyncFileDialog::new()
+                    .set_directory(GameConfig::get_config_directory().await)
+                    .set_file_name("prefix-backup.tar.gz")
+                    .save_file()
+                    .await;
+
+                if let Some(result) = result {
+                    let _ = self.settings_task.sender().send(
+                        manager::HandleSettingsTaskMsg::BackupPrefix(result.path().to_path_buf()),
+                    );
+                }
+            }
+            SettingsPageMsg::RestorePrefix => {
+                let result = rfd::AsyncFileDialog::new()
+                    .set_directory(GameConfig::get_config_directory().await)
+                    .add_filter("tar.gz", &["gz"])
+                    .pick_file()
+                    .await;
+
+                if let Some(result) = result {
+                    let _ = self.settings_task.sender().send(
+                        manager::HandleSettingsTaskMsg::RestorePrefix(result.path().to_path_buf()),
+                    );
+                }
+            }
+            SettingsPageMsg::ResetPrefix => {
+                let dialog = unsafe {
+                    adw::MessageDialog::new(
+                        MAIN_WINDOW.as_ref(),
+                        Some("Reset the wine prefix?"),
+                        Some("Everything in the wine prefix will be deleted, the game files are kept"),
+                    )
+                };
+
+                dialog.add_response("cancel", "Cancel");
+                dialog.add_response("reset", "Reset");
+
+                dialog.set_response_appearance("reset", adw::ResponseAppearance::Destructive);
+
+                let settings_task = self.settings_task.sender().clone();
+                dialog.connect_response(Some("reset"), move |_, _| {
+                    let _ = settings_task.send(manager::HandleSettingsTaskMsg::ResetPrefix);
+                });
+
+                dialog.present();
+            }
+            SettingsPageMsg::SetIsInstalling(value) => self.is_installing = value,
+            SettingsPageMsg::UpdateConfig => {
+                let config = GameConfig::get_config().await;
+                self.render_api = config.render_api;
+                self.is_vkd3d_installed = config.is_vkd3d_installed;
+                self.prefix_manifest = config.prefix_manifest;
+                self.prefix_settings = config.prefix_settings;
+            }
             SettingsPageMsg::ShowError(message) => {
                 let dialog = unsafe {
                     adw::MessageDialog::new(
@@ -120,3 +1172,184 @@ impl SimpleAsyncComponent for SettingsPage {
         }
     }
 }
+
+impl SettingsPage {
+    fn update_env_vars(&mut self, env_vars: Vec<(String, String)>) {
+        let mut guard = self.env_vars.guard();
+        guard.clear();
+        for variable in env_vars {
+            guard.push_back(variable);
+        }
+    }
+
+    async fn save_launch_options(&self, sender: &relm4::AsyncComponentSender<Self>) {
+        if let Err(e) = GameConfig::set_launch_options(self.launch_options.clone()).await {
+            sender.input(SettingsPageMsg::ShowError(format!(
+                "Something went wrong when updated the launch options : {}",
+                e
+            )));
+        }
+    }
+
+    async fn save_unity_settings(
+        &mut self,
+        settings: UnityPlayerSettings,
+        sender: &relm4::AsyncComponentSender<Self>,
+    ) {
+        match GameConfig::set_unity_settings(settings.clone()).await {
+            Ok(_) => self.unity = settings,
+            Err(e) => sender.input(SettingsPageMsg::ShowError(format!(
+                "Something went wrong when updated the window settings : {}",
+                e
+            ))),
+        }
+    }
+
+    async fn save_dxvk_settings(
+        &mut self,
+        settings: DxvkSettings,
+        sender: &relm4::AsyncComponentSender<Self>,
+    ) {
+        match GameConfig::set_dxvk_settings(settings.clone()).await {
+            Ok(_) => self.dxvk = settings,
+            Err(e) => sender.input(SettingsPageMsg::ShowError(format!(
+                "Something went wrong when updated the DXVK settings : {}",
+                e
+            ))),
+        }
+    }
+
+    async fn save_runner_settings(
+        &mut self,
+        settings: RunnerSettings,
+        sender: &relm4::AsyncComponentSender<Self>,
+    ) {
+        let runner = GameConfig::get_runner().await;
+        let wine_version = WineComponent::new(GameConfig::get_config_directory().await)
+            .init_wine(&runner)
+            .version()
+            .ok();
+
+        let result = match settings.check(&runner, wine_version.as_ref().and_then(|v| v.to_str())) {
+            Ok(_) => GameConfig::set_runner_settings(settings).await,
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(_) => self.runner_settings = settings,
+            Err(e) => sender.input(SettingsPageMsg::ShowError(format!(
+                "Something went wrong when updated the runner settings : {}",
+                e
+            ))),
+        }
+    }
+
+    async fn save_hooks(&self, sender: &relm4::AsyncComponentSender<Self>) {
+        if let Err(e) = GameConfig::set_hooks(self.hooks.clone()).await {
+            sender.input(SettingsPageMsg::ShowError(format!(
+                "Something went wrong when updated the hooks : {}",
+                e
+            )));
+        }
+    }
+
+    fn save_prefix_settings(&mut self, settings: PrefixSettings) {
+        if settings == self.prefix_settings {
+            return;
+        }
+
+        self.prefix_settings = settings.clone();
+        let _ =
+            self.settings_task
+                .sender()
+                .send(manager::HandleSettingsTaskMsg::ApplyPrefixSettings(
+                    settings,
+                ));
+    }
+
+    async fn save_runner(&self, sender: &relm4::AsyncComponentSender<Self>) {
+        let path = Some(PathBuf::from(&self.runner_path)).filter(|_| !self.runner_path.is_empty());
+
+        let runner = match (self.runner_kind, path) {
+            (0, _) => Runner::Bundled,
+            (1, _) => Runner::System,
+            (2, Some(path)) => Runner::Proton { path },
+            (3, proton_path) => Runner::Umu { proton_path },
+            (4, Some(_)) => match Runner::parse_custom_command(&self.runner_path) {
+                Ok(runner) => runner,
+                Err(_) => return, // waiting for the quotes to be closed
+            },
+            _ => return, // waiting for the path to be filled
+        };
+
+        if let Err(e) = GameConfig::set_runner(runner).await {
+            sender.input(SettingsPageMsg::ShowError(format!(
+                "Something went wrong when updated the runner : {}",
+                e
+            )));
+        }
+    }
+}
+
+fn format_entries(entries: &[ManifestEntry]) -> String {
+    entries
+        .iter()
+        .map(|e| {
+            if e.is_installed {
+                e.name.clone()
+            } else {
+                format!("{} (pending)", e.name)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[derive(Debug)]
+struct EnvVarRow {
+    name: String,
+    value: String,
+}
+
+#[relm4::factory]
+impl FactoryComponent for EnvVarRow {
+    type Init = (String, String);
+    type Input = ();
+    type Output = SettingsPageMsg;
+    type CommandOutput = ();
+    type ParentWidget = gtk::ListBox;
+
+    view! {
+        adw::ActionRow {
+            set_title: &self.name,
+            set_subtitle: &self.value,
+
+            add_suffix = &gtk::Button {
+                set_icon_name: "user-trash-symbolic",
+                set_valign: gtk::Align::Center,
+                add_css_class: "flat",
+
+                connect_clicked[sender, name = self.name.clone()] => move |_| {
+                    let _ = sender.output(SettingsPageMsg::UnsetEnvVar(name.clone()));
+                }
+            }
+        }
+    }
+
+    fn init_model(
+        (name, value): Self::Init,
+        _index: &DynamicIndex,
+        _sender: FactorySender<Self>,
+    ) -> Self {
+        Self { name, value }
+    }
+}
+
+// an empty entry resets the setting
+fn parse_optional_number(value: &str) -> Result<Option<u32>, std::num::ParseIntError> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    value.parse().map(Some)
+}
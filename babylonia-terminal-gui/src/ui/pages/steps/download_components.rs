@@ -208,7 +208,8 @@ impl SimpleAsyncComponent for DownloadComponentsPage {
                     adw::ActionRow {
                         #[watch]
                         set_title: "Fonts",
-                        set_subtitle: "Arial",
+                        #[watch]
+                        set_subtitle: &model.game_config.prefix_manifest.fonts.iter().map(|e| e.name.as_str()).collect::<Vec<&str>>().join(", "),
 
                         #[watch]
                         set_icon_name: if model.currently_installing != CurrentlyInstalling::Fonts && model.game_config.is_font_installed { Some("emblem-ok-symbolic") } else { None },
@@ -224,7 +225,8 @@ impl SimpleAsyncComponent for DownloadComponentsPage {
                     adw::ActionRow {
                         #[watch]
                         set_title: "Denpendecies",
-                        set_subtitle: "vcrun2022",
+                        #[watch]
+                        set_subtitle: &model.game_config.prefix_manifest.verbs.iter().map(|e| e.name.as_str()).collect::<Vec<&str>>().join(", "),
 
                         #[watch]
                         set_icon_name: if model.currently_installing != CurrentlyInstalling::Denpendecies && model.game_config.is_dependecies_installed { Some("emblem-ok-symbolic") } else { None },
@@ -15,5 +15,9 @@ pub trait ComponentDownloader {
     ) -> anyhow::Result<PathBuf>;
 
     #[allow(async_fn_in_trait)]
-    async fn uncompress(file: PathBuf, new_filename: PathBuf) -> anyhow::Result<()>;
+    async fn uncompress<P: Reporter + 'static>(
+        file: PathBuf,
+        new_filename: PathBuf,
+        progress: Option<Arc<P>>,
+    ) -> anyhow::Result<()>;
 }
@@ -1,28 +1,27 @@
-use std::{
-    fs::{remove_file, rename, File},
-    path::PathBuf,
-};
+use std::{path::PathBuf, sync::OnceLock};
 
 use downloader::Downloader;
-use flate2::read::GzDecoder;
-use tar::Archive;
 use tokio::fs::remove_dir_all;
 use wincompatlib::{
     dxvk::InstallParams,
     wine::{ext::WineWithExt, Wine},
 };
 
-use crate::utils::github_requester::GithubRequester;
+use crate::utils::{archive, github_requester::GithubRequester};
 
 use super::component_downloader::ComponentDownloader;
 
 pub static DXVK_DEV: &str = "doitsujin";
 pub static DXVK_REPO: &str = "dxvk";
 
+/// The dlls overridden when DXVK is installed in the prefix
+pub static DXVK_DLLS: [&str; 4] = ["d3d9", "d3d10core", "d3d11", "dxgi"];
+
 pub struct DXVKComponent<'a> {
     wine: &'a Wine,
     path: PathBuf,
     github_release_index: usize,
+    release_tag: OnceLock<String>,
 }
 
 impl<'a> DXVKComponent<'a> {
@@ -31,6 +30,7 @@ impl<'a> DXVKComponent<'a> {
             wine,
             path: path.join("dxvk"),
             github_release_index: 0,
+            release_tag: OnceLock::new(),
         }
     }
 }
@@ -39,6 +39,10 @@ impl<'a> GithubRequester for DXVKComponent<'a> {
     fn set_github_release_index(&mut self, new_release_index: usize) {
         self.github_release_index = new_release_index;
     }
+
+    fn get_github_release_tag(&self) -> Option<String> {
+        self.release_tag.get().cloned()
+    }
 }
 
 impl<'a> ComponentDownloader for DXVKComponent<'a> {
@@ -51,9 +55,9 @@ impl<'a> ComponentDownloader for DXVKComponent<'a> {
             .parent()
             .expect("Failed to get parent folder for DXVK")
             .to_path_buf();
-        let file_output = self.download(&dir, progress).await?;
+        let file_output = self.download(&dir, progress.clone()).await?;
 
-        Self::uncompress(file_output.clone(), self.path.clone()).await?;
+        Self::uncompress(file_output.clone(), self.path.clone(), progress).await?;
 
         self.wine
             .install_dxvk(self.path.clone(), InstallParams::default())
@@ -75,6 +79,7 @@ impl<'a> ComponentDownloader for DXVKComponent<'a> {
         let releases =
             Self::get_github_release_version(DXVK_DEV, DXVK_REPO, self.github_release_index)
                 .await?;
+        let _ = self.release_tag.set(releases.tag_name.clone());
 
         let asset = releases
             .assets
@@ -96,25 +101,11 @@ impl<'a> ComponentDownloader for DXVKComponent<'a> {
         Ok(file_location)
     }
 
-    async fn uncompress(
+    async fn uncompress<P: downloader::progress::Reporter + 'static>(
         file: std::path::PathBuf,
         new_directory_name: std::path::PathBuf,
+        progress: Option<std::sync::Arc<P>>,
     ) -> anyhow::Result<()> {
-        tokio::task::spawn_blocking(move || -> anyhow::Result<()> {
-            let tar_gz = File::open(file.clone())?;
-            let tar = GzDecoder::new(tar_gz);
-            let mut archive = Archive::new(tar);
-            archive.unpack(new_directory_name.parent().unwrap())?;
-            remove_file(file.clone())?;
-            rename(
-                file.to_str().unwrap().strip_suffix(".tar.gz").unwrap(),
-                new_directory_name,
-            )?;
-
-            Ok::<(), anyhow::Error>(())
-        })
-        .await??;
-
-        Ok(())
+        archive::extract_component(file, new_directory_name, progress).await
     }
 }
@@ -19,6 +19,35 @@ use crate::utils::kuro_prod_api;
 use crate::utils::kuro_prod_api::Resource;
 use crate::utils::kuro_prod_api::Resources;
 
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct VerifyReport {
+    pub checked: usize,
+    pub missing: Vec<String>,
+    pub wrong_size: Vec<String>,
+}
+
+impl VerifyReport {
+    pub fn is_valid(&self) -> bool {
+        self.missing.is_empty() && self.wrong_size.is_empty()
+    }
+}
+
+impl std::fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} files checked", self.checked)?;
+
+        for file in &self.missing {
+            writeln!(f, "missing : {}", file)?;
+        }
+
+        for file in &self.wrong_size {
+            writeln!(f, "wrong size : {}", file)?;
+        }
+
+        Ok(())
+    }
+}
+
 pub struct GameComponent {
     game_dir: PathBuf,
 }
@@ -30,6 +59,30 @@ impl GameComponent {
         }
     }
 
+    /// Compare the files of the game to the ones of the current version by their size, it's much
+    /// faster than the md5 check done when the game is installed or updated
+    pub async fn verify(&self) -> anyhow::Result<VerifyReport> {
+        let game_info = kuro_prod_api::GameInfo::get_info().await?;
+        let resources = game_info.fetch_resources().await?;
+
+        let mut report = VerifyReport {
+            checked: resources.resource.len(),
+            ..Default::default()
+        };
+
+        for resource in resources.resource {
+            match tokio::fs::metadata(self.game_dir.join(&resource.dest)).await {
+                Ok(metadata) if metadata.len() as i64 != resource.size => {
+                    report.wrong_size.push(resource.dest)
+                }
+                Ok(_) => {}
+                Err(_) => report.missing.push(resource.dest),
+            }
+        }
+
+        Ok(report)
+    }
+
     async fn check_and_get_resources_to_download(
         game_dir: &std::path::PathBuf,
         resources: &Resources,
@@ -191,9 +244,10 @@ impl ComponentDownloader for GameComponent {
         Ok(output_dir.clone())
     }
 
-    async fn uncompress(
+    async fn uncompress<P: downloader::progress::Reporter + 'static>(
         _file: std::path::PathBuf,
         _new_filename: std::path::PathBuf,
+        _progress: Option<std::sync::Arc<P>>,
     ) -> anyhow::Result<()> {
         anyhow::bail!("How did you run this function??!!")
     }
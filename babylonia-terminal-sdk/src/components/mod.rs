@@ -1,4 +1,5 @@
 pub mod component_downloader;
 pub mod dxvk_component;
 pub mod game_component;
+pub mod vkd3d_component;
 pub mod wine_component;
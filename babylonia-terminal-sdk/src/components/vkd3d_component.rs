@@ -0,0 +1,133 @@
+use std::{path::PathBuf, sync::OnceLock};
+
+use downloader::Downloader;
+use log::debug;
+use tokio::fs::{copy, read_dir, remove_dir_all};
+use wincompatlib::wine::Wine;
+
+use crate::utils::{archive, github_requester::GithubRequester, prefix_registry};
+
+use super::component_downloader::ComponentDownloader;
+
+pub static VKD3D_DEV: &str = "HansKristian-Work";
+pub static VKD3D_REPO: &str = "vkd3d-proton";
+
+pub static VKD3D_DLLS: [&str; 2] = ["d3d12", "d3d12core"];
+
+pub struct Vkd3dComponent<'a> {
+    wine: &'a Wine,
+    path: PathBuf,
+    github_release_index: usize,
+    release_tag: OnceLock<String>,
+}
+
+impl<'a> Vkd3dComponent<'a> {
+    pub fn from_wine<'b: 'a>(wine: &'b Wine, path: PathBuf) -> Self {
+        Vkd3dComponent {
+            wine,
+            path: path.join("vkd3d"),
+            github_release_index: 0,
+            release_tag: OnceLock::new(),
+        }
+    }
+
+    // same thing as the setup script shipped with vkd3d-proton, copy the dlls in the prefix and override them
+    async fn install_in_prefix(&self) -> anyhow::Result<()> {
+        let windows_dir = self.wine.prefix.join("drive_c").join("windows");
+
+        for (arch, system_dir) in [("x64", "system32"), ("x86", "syswow64")] {
+            let source_dir = self.path.join(arch);
+            let destination_dir = windows_dir.join(system_dir);
+
+            if !source_dir.exists() || !destination_dir.exists() {
+                debug!("Skipping VKD3D {} dlls", arch);
+                continue;
+            }
+
+            let mut entries = read_dir(&source_dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                debug!("Copying {:?} to {:?}", entry.path(), destination_dir);
+                copy(entry.path(), destination_dir.join(entry.file_name())).await?;
+            }
+        }
+
+        for dll in VKD3D_DLLS {
+            prefix_registry::set_dll_override(self.wine, dll, "native")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> GithubRequester for Vkd3dComponent<'a> {
+    fn set_github_release_index(&mut self, new_release_index: usize) {
+        self.github_release_index = new_release_index;
+    }
+
+    fn get_github_release_tag(&self) -> Option<String> {
+        self.release_tag.get().cloned()
+    }
+}
+
+impl<'a> ComponentDownloader for Vkd3dComponent<'a> {
+    async fn install<P: downloader::progress::Reporter + 'static>(
+        &self,
+        progress: Option<std::sync::Arc<P>>,
+    ) -> anyhow::Result<()> {
+        let dir = self
+            .path
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("Failed to get parent folder for VKD3D"))?
+            .to_path_buf();
+        let file_output = self.download(&dir, progress.clone()).await?;
+
+        Self::uncompress(file_output.clone(), self.path.clone(), progress).await?;
+
+        self.install_in_prefix().await?;
+
+        //clean the directory
+        remove_dir_all(self.path.clone()).await?;
+
+        Ok(())
+    }
+
+    async fn download<P: downloader::progress::Reporter + 'static>(
+        &self,
+        output_dir: &std::path::PathBuf,
+        progress: Option<std::sync::Arc<P>>,
+    ) -> anyhow::Result<std::path::PathBuf> {
+        let releases =
+            Self::get_github_release_version(VKD3D_DEV, VKD3D_REPO, self.github_release_index)
+                .await?;
+        let _ = self.release_tag.set(releases.tag_name.clone());
+
+        let asset = releases.assets.first().ok_or_else(|| {
+            anyhow::anyhow!(
+                "No asset found in the {} release of VKD3D-Proton",
+                releases.tag_name
+            )
+        })?;
+
+        let mut downloader = Downloader::builder()
+            .download_folder(output_dir)
+            .parallel_requests(1)
+            .build()?;
+        let mut dl = downloader::Download::new(&asset.browser_download_url);
+        if let Some(p) = progress {
+            dl = dl.progress(p);
+        }
+
+        let _result = downloader.async_download(&[dl]).await?;
+        let file_location = output_dir.join(asset.name.clone());
+
+        Ok(file_location)
+    }
+
+    async fn uncompress<P: downloader::progress::Reporter + 'static>(
+        file: std::path::PathBuf,
+        new_directory_name: std::path::PathBuf,
+        progress: Option<std::sync::Arc<P>>,
+    ) -> anyhow::Result<()> {
+        archive::extract_component(file, new_directory_name, progress).await
+    }
+}
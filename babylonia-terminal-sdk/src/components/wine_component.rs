@@ -1,18 +1,20 @@
 use std::{
-    fs::{remove_file, rename, File},
-    path::PathBuf,
+    fmt::Display,
+    path::{Path, PathBuf},
     str::FromStr,
-    sync::Arc,
+    sync::{Arc, OnceLock},
 };
 
 use downloader::{progress::Reporter, Downloader};
-use log::debug;
-use tar::Archive;
-use wincompatlib::wine::ext::WineBootExt;
-use xz2::read::XzDecoder;
+use log::{debug, info};
+use tokio::fs::create_dir_all;
+use wincompatlib::wine::{ext::WineBootExt, Wine};
 
 use super::component_downloader::ComponentDownloader;
-use crate::utils::github_requester::GithubRequester;
+use crate::{
+    runner::Runner,
+    utils::{archive, github_requester::GithubRequester},
+};
 
 pub static WINE_DEV: &str = "Kron4ek";
 pub static WINE_REPO: &str = "Wine-Builds";
@@ -21,12 +23,55 @@ pub static WINE_REPO: &str = "Wine-Builds";
 pub struct WineComponent {
     path: PathBuf,
     github_release_index: usize,
+    release_tag: OnceLock<String>,
 }
 
 impl GithubRequester for WineComponent {
     fn set_github_release_index(&mut self, new_release_index: usize) {
         self.github_release_index = new_release_index;
     }
+
+    fn get_github_release_tag(&self) -> Option<String> {
+        self.release_tag.get().cloned()
+    }
+}
+
+#[derive(Debug)]
+pub enum PrefixError {
+    WineNotFound(PathBuf),
+    WinebootFailed(String),
+    // wineboot panicked or was cancelled
+    Interrupted(tokio::task::JoinError),
+    Io(std::io::Error),
+    Config(anyhow::Error),
+}
+
+impl Display for PrefixError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrefixError::WineNotFound(path) => {
+                write!(f, "wine was not found at {}", path.display())
+            }
+            PrefixError::WinebootFailed(e) => write!(f, "wineboot failed : {}", e),
+            PrefixError::Interrupted(e) => {
+                write!(
+                    f,
+                    "the initialization of the prefix was interrupted : {}",
+                    e
+                )
+            }
+            PrefixError::Io(e) => write!(f, "failed to create the wine prefix : {}", e),
+            PrefixError::Config(e) => write!(f, "failed to update the config : {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PrefixError {}
+
+impl From<std::io::Error> for PrefixError {
+    fn from(value: std::io::Error) -> Self {
+        PrefixError::Io(value)
+    }
 }
 
 impl ComponentDownloader for WineComponent {
@@ -38,10 +83,10 @@ impl ComponentDownloader for WineComponent {
                     .parent()
                     .expect("Failed to get the parent directory of wine")
                     .to_path_buf(),
-                progress,
+                progress.clone(),
             )
             .await?;
-        Self::uncompress(file_output.clone(), self.path.clone()).await?;
+        Self::uncompress(file_output.clone(), self.path.clone(), progress).await?;
 
         Ok(())
     }
@@ -54,6 +99,7 @@ impl ComponentDownloader for WineComponent {
         let release =
             Self::get_github_release_version(WINE_DEV, WINE_REPO, self.github_release_index)
                 .await?;
+        let _ = self.release_tag.set(release.tag_name.clone());
 
         let asset = release
             .assets
@@ -76,23 +122,12 @@ impl ComponentDownloader for WineComponent {
         Ok(file_location)
     }
 
-    async fn uncompress(file: PathBuf, new_directory_name: PathBuf) -> anyhow::Result<()> {
-        tokio::task::spawn_blocking(move || {
-            let tar_xz = File::open(file.clone()).unwrap();
-            let tar = XzDecoder::new(tar_xz);
-            let mut archive = Archive::new(tar);
-            archive.unpack(new_directory_name.parent().unwrap())?;
-            remove_file(file.clone())?;
-            rename(
-                file.to_str().unwrap().strip_suffix(".tar.xz").unwrap(),
-                new_directory_name,
-            )?;
-
-            Ok::<(), anyhow::Error>(())
-        })
-        .await??;
-
-        Ok(())
+    async fn uncompress<P: Reporter + 'static>(
+        file: PathBuf,
+        new_directory_name: PathBuf,
+        progress: Option<Arc<P>>,
+    ) -> anyhow::Result<()> {
+        archive::extract_component(file, new_directory_name, progress).await
     }
 }
 
@@ -101,21 +136,65 @@ impl WineComponent {
         WineComponent {
             path: path.join("wine"),
             github_release_index: 0,
+            release_tag: OnceLock::new(),
         }
     }
 
-    pub fn init_wine(&self) -> Result<wincompatlib::prelude::Wine, String> {
+    /// The prefix isn't created here, see [`WineComponent::init_prefix`]
+    pub fn init_wine(&self, runner: &Runner) -> Wine {
         let prefix = self.path.parent().unwrap().join("data");
-        let wine_bin_location = self.path.join("bin/wine");
-        debug!("Initializing prefix with -> {:?}", prefix);
+        let wine_bin_location = runner.get_wine_binary(self.path.parent().unwrap());
+        debug!("Wine prefix path : {:?}", prefix);
         debug!("Wine binary path : {:?}", wine_bin_location);
 
-        let mut wine = wincompatlib::prelude::Wine::from_binary(wine_bin_location);
-        wine.prefix = prefix.clone();
+        let mut wine = Wine::from_binary(wine_bin_location);
+        wine.prefix = prefix;
+
+        wine
+    }
+
+    // wineboot write the registry at the end of the initialization
+    pub fn is_prefix_initialized(prefix: &Path) -> bool {
+        prefix.join("system.reg").is_file()
+    }
+
+    /// Run wineboot in the prefix of `wine` if it's not already initialized,
+    /// return false if there was nothing to do
+    pub async fn init_prefix<P: Reporter + 'static>(
+        wine: &Wine,
+        progress: Option<Arc<P>>,
+    ) -> Result<bool, PrefixError> {
+        if Self::is_prefix_initialized(&wine.prefix) {
+            debug!("The wine prefix is already initialized");
+            return Ok(false);
+        }
 
-        wine.init_prefix(Some(prefix)).unwrap();
+        // a relative binary is searched in the $PATH by wine itself
+        if wine.binary.is_absolute() && !wine.binary.is_file() {
+            return Err(PrefixError::WineNotFound(wine.binary.clone()));
+        }
+
+        info!("Initializing the wine prefix {:?}...", wine.prefix);
+        if let Some(p) = &progress {
+            p.setup(Some(1), "Initializing the wine prefix");
+            p.progress(0);
+        }
+
+        create_dir_all(&wine.prefix).await?;
+
+        let wine = wine.clone();
+        let prefix = wine.prefix.clone();
+        tokio::task::spawn_blocking(move || wine.init_prefix(Some(prefix)))
+            .await
+            .map_err(PrefixError::Interrupted)?
+            .map_err(|e| PrefixError::WinebootFailed(e.to_string()))?;
+
+        if let Some(p) = &progress {
+            p.progress(1);
+            p.done();
+        }
 
-        Ok(wine)
+        Ok(true)
     }
 
     //fn get_steam_location() -> Result<PathBuf, String> {
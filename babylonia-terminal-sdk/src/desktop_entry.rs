@@ -0,0 +1,112 @@
+use std::path::{Path, PathBuf};
+
+use log::debug;
+use tokio::fs::{create_dir_all, remove_file, write};
+
+use crate::utils::get_game_name;
+
+pub const DESKTOP_ENTRY_ID: &str = "moe.celica.BabyloniaTerminal.Game";
+
+// a 256x256 copy of the icon of the GUI, kept out of `assets` which is embedded by the patcher
+pub(crate) const ICON: &[u8] = include_bytes!("../icons/icon.png");
+
+fn get_data_directory() -> anyhow::Result<PathBuf> {
+    dirs::data_dir().ok_or_else(|| anyhow::anyhow!("Failed to find the data directory of the user"))
+}
+
+pub fn get_desktop_entry_path() -> anyhow::Result<PathBuf> {
+    Ok(get_data_directory()?
+        .join("applications")
+        .join(format!("{}.desktop", DESKTOP_ENTRY_ID)))
+}
+
+pub fn get_icon_path() -> anyhow::Result<PathBuf> {
+    Ok(get_data_directory()?
+        .join("icons/hicolor/256x256/apps")
+        .join(format!("{}.png", DESKTOP_ENTRY_ID)))
+}
+
+// icon path used by the previous versions
+fn get_legacy_icon_path() -> anyhow::Result<PathBuf> {
+    Ok(get_data_directory()?
+        .join("icons/hicolor/scalable/apps")
+        .join(format!("{}.png", DESKTOP_ENTRY_ID)))
+}
+
+pub fn is_installed() -> bool {
+    get_desktop_entry_path().is_ok_and(|p| p.exists())
+}
+
+/// Add the game to the application menu, `executable` is started without argument to launch the
+/// game and with `--gui` to open the launcher
+pub async fn install(executable: &Path) -> anyhow::Result<()> {
+    let icon_path = get_icon_path()?;
+    create_dir_all(icon_path.parent().unwrap()).await?;
+    write(&icon_path, ICON).await?;
+    let _ = remove_file(get_legacy_icon_path()?).await;
+
+    let entry_path = get_desktop_entry_path()?;
+    create_dir_all(entry_path.parent().unwrap()).await?;
+    write(&entry_path, get_desktop_entry(executable)).await?;
+
+    debug!("Desktop entry written to {:?}", entry_path);
+    Ok(())
+}
+
+/// Return false if the game wasn't in the application menu
+pub async fn uninstall() -> anyhow::Result<bool> {
+    let _ = remove_file(get_icon_path()?).await;
+    let _ = remove_file(get_legacy_icon_path()?).await;
+
+    let entry_path = get_desktop_entry_path()?;
+    if !entry_path.exists() {
+        return Ok(false);
+    }
+
+    remove_file(entry_path).await?;
+    Ok(true)
+}
+
+fn get_desktop_entry(executable: &Path) -> String {
+    let exec = quote_exec_arg(&executable.to_string_lossy());
+
+    format!(
+        "[Desktop Entry]
+Type=Application
+Name={name}
+Comment=Start {name} with Babylonia Terminal
+Icon={icon}
+Exec={exec}
+Terminal=false
+Categories=Game;
+Actions=Launcher;
+
+[Desktop Action Launcher]
+Name=Open the launcher
+Exec={exec} --gui
+",
+        name = get_game_name(),
+        icon = DESKTOP_ENTRY_ID,
+        exec = exec,
+    )
+}
+
+// the quoting of the Exec key is escaped a second time by the string unescaping of the file
+fn quote_exec_arg(arg: &str) -> String {
+    let mut quoted = String::from("\"");
+
+    for c in arg.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\\\\\"),
+            '"' | '`' | '$' => {
+                quoted.push_str("\\\\");
+                quoted.push(c);
+            }
+            '%' => quoted.push_str("%%"),
+            _ => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
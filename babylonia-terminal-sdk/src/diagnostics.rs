@@ -0,0 +1,146 @@
+use std::path::{Path, PathBuf};
+
+use log::{debug, warn};
+use serde_json::Value;
+use tokio::fs::{copy, create_dir_all, read_dir, remove_dir_all, write};
+use wincompatlib::wine::Wine;
+
+use crate::{
+    components::game_component::GameComponent, game_config::GameConfig, game_logs::LogSession,
+    utils::archive,
+};
+
+// the logs of the latest launches added to the bundle
+static LOG_SESSIONS_COUNT: usize = 3;
+static REDACTED: &str = "<redacted>";
+
+/// Write a tar.gz with what's needed to investigate a bug : the config without its secrets, the
+/// versions, the system, the latest logs and a check of the game files
+pub async fn export(wine: Option<&Wine>, output: PathBuf) -> anyhow::Result<()> {
+    let staging_dir = std::env::temp_dir()
+        .join(format!("babylonia-terminal-{}", std::process::id()))
+        .join("babylonia-terminal-diagnostics");
+    if staging_dir.exists() {
+        remove_dir_all(&staging_dir).await?;
+    }
+    create_dir_all(&staging_dir).await?;
+
+    let result = write_bundle(wine, &staging_dir).await;
+    let result = match result {
+        Ok(_) => archive::compress_dir(staging_dir.clone(), output).await,
+        Err(e) => Err(e),
+    };
+
+    let _ = remove_dir_all(staging_dir.parent().unwrap()).await;
+    result
+}
+
+async fn write_bundle(wine: Option<&Wine>, dir: &Path) -> anyhow::Result<()> {
+    let config = GameConfig::get_config().await;
+
+    write(dir.join("config.json"), get_redacted_config(&config)?).await?;
+    write(dir.join("system.txt"), get_system_info(wine)).await?;
+
+    let verify_report = match &config.game_dir {
+        Some(game_dir) => match GameComponent::new(game_dir.clone()).verify().await {
+            Ok(report) => report.to_string(),
+            Err(e) => format!("Failed to verify the game files : {}", e),
+        },
+        None => "The game is not installed".to_string(),
+    };
+    write(dir.join("verify.txt"), verify_report).await?;
+
+    let logs_dir = dir.join("logs");
+    create_dir_all(&logs_dir).await?;
+    for session in LogSession::list()
+        .await?
+        .into_iter()
+        .take(LOG_SESSIONS_COUNT)
+    {
+        if let Err(e) = copy_session(&session, &logs_dir.join(session.get_id())).await {
+            warn!("Failed to add the logs of {} : {}", session.get_id(), e);
+        }
+    }
+
+    Ok(())
+}
+
+fn get_redacted_config(config: &GameConfig) -> anyhow::Result<String> {
+    let mut value = serde_json::to_value(config)?;
+
+    if let Some(env_vars) = value
+        .pointer_mut("/launch_options/env_vars")
+        .and_then(Value::as_array_mut)
+    {
+        for variable in env_vars.iter_mut().filter_map(Value::as_array_mut) {
+            if let Some(value) = variable.get_mut(1) {
+                *value = REDACTED.into();
+            }
+        }
+    }
+
+    // the commands and the arguments can contain tokens or urls
+    for pointer in [
+        "/launch_options/raw",
+        "/launch_options/gamescope",
+        "/launch_options/game_args",
+        "/hooks/pre_launch",
+        "/hooks/post_exit",
+        "/runner/Custom/args",
+    ] {
+        if let Some(value) = value.pointer_mut(pointer) {
+            redact(value);
+        }
+    }
+
+    let content = serde_json::to_string_pretty(&value)?;
+
+    // the name of the user is often in the home directory
+    Ok(match dirs::home_dir().as_deref().and_then(Path::to_str) {
+        Some(home) => content.replace(home, "~"),
+        None => content,
+    })
+}
+
+// the strings are replaced one by one, so it's still visible what is set
+fn redact(value: &mut Value) {
+    match value {
+        Value::String(_) => *value = REDACTED.into(),
+        Value::Array(values) => values.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+fn get_system_info(wine: Option<&Wine>) -> String {
+    let wine_version = match wine.map(|w| w.version()) {
+        Some(Ok(version)) => version.to_string_lossy().to_string(),
+        Some(Err(e)) => format!("unknown ({})", e),
+        None => "unknown".to_string(),
+    };
+
+    let kernel = std::fs::read_to_string("/proc/sys/kernel/osrelease")
+        .map(|k| k.trim().to_string())
+        .unwrap_or_else(|_| "unknown".to_string());
+
+    format!(
+        "launcher version : {}\ndistro : {:?}\nkernel : {}\nwine version : {}\n",
+        env!("CARGO_PKG_VERSION"),
+        whatadistro::identify(),
+        kernel,
+        wine_version
+    )
+}
+
+async fn copy_session(session: &LogSession, output: &Path) -> anyhow::Result<()> {
+    debug!("Adding the logs of {}", session.get_id());
+    create_dir_all(output).await?;
+
+    let mut entries = read_dir(&session.path).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        if entry.file_type().await?.is_file() {
+            copy(entry.path(), output.join(entry.file_name())).await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use std::path::{Path, PathBuf};
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+use tokio::fs::{create_dir_all, read_dir, remove_dir_all, remove_file, write};
+
+use crate::utils::get_game_name;
+
+pub static DXVK_CONFIG_FILE: &str = "dxvk.conf";
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DxvkSettings {
+    /// The value of `DXVK_HUD`, like `fps,frametimes`
+    pub hud: Option<String>,
+    /// `dxgi.maxFrameRate`, also passed to VKD3D-Proton
+    pub frame_limit: Option<u32>,
+    /// `dxgi.maxFrameLatency`, from 1 to 16
+    pub max_frame_latency: Option<u32>,
+    /// The other options of `dxvk.conf`, like `dxgi.syncInterval` = `0`
+    pub options: Vec<(String, String)>,
+}
+
+impl DxvkSettings {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.frame_limit == Some(0) {
+            anyhow::bail!("The frame limit needs to be at least 1, or disabled");
+        }
+
+        if self
+            .max_frame_latency
+            .is_some_and(|l| !(1..=16).contains(&l))
+        {
+            anyhow::bail!("The maximum frame latency needs to be between 1 and 16");
+        }
+
+        for (name, value) in &self.options {
+            if name.is_empty() || name.contains(|c: char| c == '=' || c.is_whitespace()) {
+                anyhow::bail!("Invalid DXVK option '{}'", name);
+            }
+
+            if value.contains('\n') {
+                anyhow::bail!(
+                    "The value of the DXVK option '{}' can't contain a new line",
+                    name
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Return none if there's nothing to write to `dxvk.conf`
+    pub fn to_config(&self) -> Option<String> {
+        let mut lines = vec![];
+
+        if let Some(limit) = self.frame_limit {
+            lines.push(format!("dxgi.maxFrameRate = {}", limit));
+        }
+
+        if let Some(latency) = self.max_frame_latency {
+            lines.push(format!("dxgi.maxFrameLatency = {}", latency));
+        }
+
+        for (name, value) in &self.options {
+            lines.push(format!("{} = {}", name, value));
+        }
+
+        if lines.is_empty() {
+            return None;
+        }
+
+        Some(format!(
+            "# Written by Babylonia Terminal, the changes are overwritten on each launch\n{}\n",
+            lines.join("\n")
+        ))
+    }
+
+    /// Replace the value if the option is already set
+    pub fn set_option(&mut self, name: &str, value: &str) {
+        match self.options.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = value.to_string(),
+            None => self.options.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// Return false if the option wasn't set
+    pub fn unset_option(&mut self, name: &str) -> bool {
+        let len = self.options.len();
+        self.options.retain(|(n, _)| n != name);
+        len != self.options.len()
+    }
+}
+
+pub fn get_config_path(config_dir: &Path) -> PathBuf {
+    config_dir.join(DXVK_CONFIG_FILE)
+}
+
+pub fn get_dxvk_cache_dir(config_dir: &Path) -> PathBuf {
+    config_dir.join("cache").join("dxvk")
+}
+
+pub fn get_vkd3d_cache_dir(config_dir: &Path) -> PathBuf {
+    config_dir.join("cache").join("vkd3d")
+}
+
+/// Write `dxvk.conf` and create the cache directories, the file is removed if there's nothing
+/// to write in it
+pub async fn write_config(config_dir: &Path, settings: &DxvkSettings) -> anyhow::Result<()> {
+    create_dir_all(get_dxvk_cache_dir(config_dir)).await?;
+    create_dir_all(get_vkd3d_cache_dir(config_dir)).await?;
+
+    let path = get_config_path(config_dir);
+    match settings.to_config() {
+        Some(content) => write(&path, content).await?,
+        None if path.exists() => remove_file(&path).await?,
+        None => {}
+    }
+
+    Ok(())
+}
+
+/// Remove the caches of DXVK and VKD3D-Proton, and the DXVK caches written next to the game
+/// before they had their own directory, return how many files were removed
+pub async fn clear_shader_cache(
+    config_dir: &Path,
+    game_dir: Option<&Path>,
+) -> anyhow::Result<usize> {
+    let mut count = 0;
+
+    for dir in [
+        get_dxvk_cache_dir(config_dir),
+        get_vkd3d_cache_dir(config_dir),
+    ] {
+        if !dir.exists() {
+            continue;
+        }
+
+        let mut entries = read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_type().await?.is_dir() {
+                remove_dir_all(entry.path()).await?;
+            } else {
+                remove_file(entry.path()).await?;
+            }
+            count += 1;
+        }
+    }
+
+    if let Some(game_dir) = game_dir {
+        let legacy_cache = game_dir
+            .join(get_game_name())
+            .join(format!("{}.dxvk-cache", get_game_name()));
+
+        if legacy_cache.exists() {
+            remove_file(&legacy_cache).await?;
+            count += 1;
+        }
+    }
+
+    debug!("{} shader cache files removed", count);
+    Ok(count)
+}
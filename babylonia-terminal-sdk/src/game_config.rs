@@ -1,7 +1,19 @@
-use std::path::PathBuf;
+use std::{collections::BTreeMap, fmt::Display, path::PathBuf, str::FromStr};
 
 use dirs::home_dir;
 use serde::{Deserialize, Serialize};
+
+use crate::{
+    dxvk_config::DxvkSettings,
+    game_manager::EnvironmentVariable,
+    hooks::LaunchHooks,
+    launch_options::{self, LaunchOptions},
+    prefix_manifest::PrefixManifest,
+    prefix_settings::PrefixSettings,
+    runner::Runner,
+    runner_settings::RunnerSettings,
+    unity_player::UnityPlayerSettings,
+};
 use tokio::{
     fs::{create_dir_all, read_to_string, File},
     io::AsyncWriteExt,
@@ -12,12 +24,42 @@ pub struct GameConfig {
     pub config_dir: PathBuf,
     pub is_wine_installed: bool,
     pub is_dxvk_installed: bool,
+    #[serde(default)]
+    pub is_vkd3d_installed: bool,
     pub is_font_installed: bool,
     pub is_dependecies_installed: bool,
     pub game_dir: Option<PathBuf>,
     pub is_game_installed: bool,
     pub is_game_patched: bool,
-    pub launch_options: Option<String>,
+    #[serde(default, deserialize_with = "launch_options::deserialize")]
+    pub launch_options: LaunchOptions,
+    #[serde(default)]
+    pub render_api: RenderApi,
+    #[serde(default)]
+    pub runner: Runner,
+    #[serde(default)]
+    pub winetricks_path: Option<PathBuf>,
+    #[serde(default)]
+    pub prefix_manifest: PrefixManifest,
+    #[serde(default)]
+    pub components: ComponentVersions,
+    #[serde(default)]
+    pub prefix_settings: PrefixSettings,
+    #[serde(default)]
+    pub is_prefix_settings_applied: bool,
+    /// The mode of the dlls before they were overridden by the prefix settings
+    #[serde(default)]
+    pub replaced_dll_overrides: BTreeMap<String, Option<String>>,
+    #[serde(default)]
+    pub dxvk: DxvkSettings,
+    #[serde(default)]
+    pub logs: LogSettings,
+    #[serde(default)]
+    pub hooks: LaunchHooks,
+    #[serde(default)]
+    pub unity: UnityPlayerSettings,
+    #[serde(default)]
+    pub runner_settings: RunnerSettings,
     pub launcher_version: Option<String>,
 }
 
@@ -30,6 +72,10 @@ impl GameConfig {
         path
     }
 
+    pub async fn get_prefix_directory() -> PathBuf {
+        Self::get_config_directory().await.join("data")
+    }
+
     async fn get_config_file_path() -> PathBuf {
         Self::get_config_directory()
             .await
@@ -66,21 +112,247 @@ impl GameConfig {
             Err(_) => return Self::default(),
             Ok(c) => c,
         };
-        match serde_json::from_str::<Self>(&content) {
-            Ok(config) => return config,
-            Err(_) => return Self::default(),
+        let Ok(content) = serde_json::from_str::<serde_json::Value>(&content) else {
+            return Self::default();
+        };
+        let has_prefix_manifest = content.get("prefix_manifest").is_some();
+
+        match serde_json::from_value::<Self>(content) {
+            Ok(mut config) => {
+                // the fonts and the dependencies already installed are not installed again
+                if !has_prefix_manifest {
+                    config.prefix_manifest = PrefixManifest::from_legacy(
+                        config.is_font_installed,
+                        config.is_dependecies_installed,
+                    );
+                }
+
+                config
+            }
+            Err(_) => Self::default(),
         }
     }
 
-    pub async fn set_launch_options(command: Option<String>) -> anyhow::Result<()> {
+    pub async fn set_launch_options(launch_options: LaunchOptions) -> anyhow::Result<()> {
+        let mut config = Self::get_config().await;
+        config.launch_options = launch_options;
+        Self::save_config(config).await?;
+        Ok(())
+    }
+
+    pub async fn get_launch_options() -> LaunchOptions {
+        Self::get_config().await.launch_options
+    }
+
+    pub async fn set_env_var(variable: EnvironmentVariable) -> anyhow::Result<()> {
+        let mut config = Self::get_config().await;
+        config.launch_options.set_env_var(variable);
+        Self::save_config(config).await?;
+        Ok(())
+    }
+
+    /// Return false if the variable wasn't set
+    pub async fn unset_env_var(name: &str) -> anyhow::Result<bool> {
+        let mut config = Self::get_config().await;
+        let is_removed = config.launch_options.unset_env_var(name);
+        Self::save_config(config).await?;
+        Ok(is_removed)
+    }
+
+    pub async fn get_env_vars() -> Vec<(String, String)> {
+        Self::get_config().await.launch_options.env_vars
+    }
+
+    pub async fn set_render_api(render_api: RenderApi) -> anyhow::Result<()> {
+        let mut config = Self::get_config().await;
+        config.render_api = render_api;
+        Self::save_config(config).await?;
+        Ok(())
+    }
+
+    pub async fn get_render_api() -> RenderApi {
+        Self::get_config().await.render_api
+    }
+
+    pub async fn set_unity_settings(settings: UnityPlayerSettings) -> anyhow::Result<()> {
+        settings.validate()?;
+
+        let mut config = Self::get_config().await;
+        config.unity = settings;
+        Self::save_config(config).await?;
+        Ok(())
+    }
+
+    pub async fn get_unity_settings() -> UnityPlayerSettings {
+        Self::get_config().await.unity
+    }
+
+    /// Checked with [`RunnerSettings::check`] before, it needs the version of wine
+    pub async fn set_runner_settings(settings: RunnerSettings) -> anyhow::Result<()> {
+        let mut config = Self::get_config().await;
+        config.runner_settings = settings;
+        Self::save_config(config).await?;
+        Ok(())
+    }
+
+    pub async fn get_runner_settings() -> RunnerSettings {
+        Self::get_config().await.runner_settings
+    }
+
+    pub async fn set_runner(runner: Runner) -> anyhow::Result<()> {
+        let mut config = Self::get_config().await;
+        config.runner = runner;
+        Self::save_config(config).await?;
+        Ok(())
+    }
+
+    pub async fn get_runner() -> Runner {
+        Self::get_config().await.runner
+    }
+
+    pub async fn set_winetricks_path(path: Option<PathBuf>) -> anyhow::Result<()> {
+        let mut config = Self::get_config().await;
+        config.winetricks_path = path;
+        Self::save_config(config).await?;
+        Ok(())
+    }
+
+    pub async fn set_dxvk_settings(settings: DxvkSettings) -> anyhow::Result<()> {
+        settings.validate()?;
+
         let mut config = Self::get_config().await;
-        config.launch_options = command;
+        config.dxvk = settings;
         Self::save_config(config).await?;
         Ok(())
     }
 
-    pub async fn get_launch_options() -> anyhow::Result<Option<String>> {
-        Ok(Self::get_config().await.launch_options)
+    pub async fn get_dxvk_settings() -> DxvkSettings {
+        Self::get_config().await.dxvk
+    }
+
+    pub async fn set_log_settings(settings: LogSettings) -> anyhow::Result<()> {
+        let mut config = Self::get_config().await;
+        config.logs = settings;
+        Self::save_config(config).await?;
+        Ok(())
+    }
+
+    pub async fn get_log_settings() -> LogSettings {
+        Self::get_config().await.logs
+    }
+
+    pub async fn set_hooks(hooks: LaunchHooks) -> anyhow::Result<()> {
+        let mut config = Self::get_config().await;
+        config.hooks = hooks;
+        Self::save_config(config).await?;
+        Ok(())
+    }
+
+    pub async fn get_hooks() -> LaunchHooks {
+        Self::get_config().await.hooks
+    }
+
+    pub async fn get_prefix_settings() -> PrefixSettings {
+        Self::get_config().await.prefix_settings
+    }
+
+    pub async fn get_prefix_manifest() -> PrefixManifest {
+        Self::get_config().await.prefix_manifest
+    }
+
+    /// Return false if the verb was already in the manifest
+    pub async fn add_winetricks_verb(verb: &str) -> anyhow::Result<bool> {
+        let mut config = Self::get_config().await;
+        let is_added = config.prefix_manifest.add_verb(verb);
+        Self::save_config(config).await?;
+        Ok(is_added)
+    }
+
+    pub async fn remove_winetricks_verb(verb: &str) -> anyhow::Result<()> {
+        let mut config = Self::get_config().await;
+        config.prefix_manifest.remove_verb(verb);
+        Self::save_config(config).await?;
+        Ok(())
+    }
+
+    /// Return false if the font was already in the manifest
+    pub async fn add_font(font: &str) -> anyhow::Result<bool> {
+        let mut config = Self::get_config().await;
+        let is_added = config.prefix_manifest.add_font(font);
+        Self::save_config(config).await?;
+        Ok(is_added)
+    }
+
+    pub async fn remove_font(font: &str) -> anyhow::Result<()> {
+        let mut config = Self::get_config().await;
+        config.prefix_manifest.remove_font(font);
+        Self::save_config(config).await?;
+        Ok(())
+    }
+}
+
+/// The github release tag of the installed components, used to reinstall the same versions
+/// when the prefix is reset
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentVersions {
+    pub wine: Option<String>,
+    pub dxvk: Option<String>,
+    pub vkd3d: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogSettings {
+    /// How many launches of the game keep their logs
+    pub retention: usize,
+    /// Copy the `Player.log` written by Unity in the prefix with the logs of the launch
+    pub collect_unity_log: bool,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        LogSettings {
+            retention: 10,
+            collect_unity_log: true,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RenderApi {
+    #[default]
+    D3D11,
+    D3D12,
+}
+
+impl RenderApi {
+    // D3D11 is the default of the game, so there's no need to force it
+    pub fn get_game_argument(&self) -> Option<&str> {
+        match self {
+            RenderApi::D3D11 => None,
+            RenderApi::D3D12 => Some("-force-d3d12"),
+        }
+    }
+}
+
+impl Display for RenderApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenderApi::D3D11 => write!(f, "d3d11"),
+            RenderApi::D3D12 => write!(f, "d3d12"),
+        }
+    }
+}
+
+impl FromStr for RenderApi {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "d3d11" | "dx11" => Ok(RenderApi::D3D11),
+            "d3d12" | "dx12" => Ok(RenderApi::D3D12),
+            _ => anyhow::bail!("Unknown render API '{}', expected d3d11 or d3d12", s),
+        }
     }
 }
 
@@ -95,12 +367,26 @@ impl Default for GameConfig {
             config_dir: dirs::home_dir().unwrap().join(".babylonia-terminal"),
             is_wine_installed: false,
             is_dxvk_installed: false,
+            is_vkd3d_installed: false,
             is_font_installed: false,
             is_dependecies_installed: false,
             game_dir: None,
             is_game_installed: false,
             is_game_patched: false,
-            launch_options: None,
+            launch_options: LaunchOptions::default(),
+            render_api: RenderApi::default(),
+            runner: Runner::default(),
+            winetricks_path: None,
+            prefix_manifest: PrefixManifest::default(),
+            components: ComponentVersions::default(),
+            prefix_settings: PrefixSettings::default(),
+            is_prefix_settings_applied: false,
+            replaced_dll_overrides: BTreeMap::new(),
+            dxvk: DxvkSettings::default(),
+            logs: LogSettings::default(),
+            hooks: LaunchHooks::default(),
+            unity: UnityPlayerSettings::default(),
+            runner_settings: RunnerSettings::default(),
             launcher_version: Some(env!("CARGO_PKG_VERSION").to_string()),
         }
     }
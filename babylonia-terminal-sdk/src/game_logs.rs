@@ -0,0 +1,182 @@
+use std::{
+    path::{Path, PathBuf},
+    process::ExitStatus,
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Local, TimeDelta};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use tokio::fs::{copy, create_dir_all, read_dir, read_to_string, remove_dir_all, write};
+
+use crate::{game_config::GameConfig, utils::kuro_prod_api::GameInfo};
+
+pub static GAME_LOG_FILE: &str = "game.log";
+pub static UNITY_LOG_FILE: &str = "Player.log";
+static SESSION_INFO_FILE: &str = "session.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub started_at: DateTime<Local>,
+    /// Not set while the game is running, or if the launcher was closed before the game
+    pub ended_at: Option<DateTime<Local>>,
+    /// Not set if the game was killed by a signal
+    pub exit_code: Option<i32>,
+    #[serde(default)]
+    pub runner: Option<String>,
+    #[serde(default)]
+    pub game_version: Option<String>,
+}
+
+impl SessionInfo {
+    /// `None` while the game is running
+    pub fn get_duration(&self) -> Option<TimeDelta> {
+        self.ended_at.map(|ended_at| ended_at - self.started_at)
+    }
+}
+
+/// The logs of a launch of the game, kept in their own directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogSession {
+    pub path: PathBuf,
+    pub info: SessionInfo,
+    // where the Unity log is searched when the game exits
+    unity_log_prefix: Option<PathBuf>,
+}
+
+impl LogSession {
+    pub async fn get_logs_directory() -> PathBuf {
+        GameConfig::get_config_directory().await.join("logs")
+    }
+
+    /// Create the directory of a new session, the oldest sessions are removed to keep the
+    /// retention count of the config
+    pub async fn create(prefix: &Path) -> anyhow::Result<Self> {
+        let config = GameConfig::get_config().await;
+        let settings = config.logs;
+        Self::prune(settings.retention.max(1) - 1).await?;
+
+        let started_at = Local::now();
+        let path = Self::get_logs_directory()
+            .await
+            .join(started_at.format("%Y-%m-%d_%H-%M-%S").to_string());
+        create_dir_all(&path).await?;
+
+        let session = Self {
+            path,
+            info: SessionInfo {
+                started_at,
+                ended_at: None,
+                exit_code: None,
+                runner: Some(config.runner.to_string()),
+                // the state of the game is checked before it's started, so the cache is up to date
+                game_version: GameInfo::get_info().await.ok().map(|i| i.default.version),
+            },
+            unity_log_prefix: settings.collect_unity_log.then(|| prefix.to_path_buf()),
+        };
+        session.save_info().await?;
+
+        Ok(session)
+    }
+
+    pub fn get_id(&self) -> String {
+        self.path.file_name().unwrap().to_string_lossy().to_string()
+    }
+
+    pub fn get_game_log_path(&self) -> PathBuf {
+        self.path.join(GAME_LOG_FILE)
+    }
+
+    /// Record how and when the game exited and collect the Unity log of the session
+    pub async fn finish(
+        &mut self,
+        status: &ExitStatus,
+        ended_at: DateTime<Local>,
+    ) -> anyhow::Result<()> {
+        self.info.ended_at = Some(ended_at);
+        self.info.exit_code = status.code();
+        self.save_info().await?;
+
+        if let Some(prefix) = &self.unity_log_prefix {
+            match find_unity_log(prefix, self.info.started_at.into()) {
+                Some(log) => {
+                    debug!("Collecting the Unity log {:?}", log);
+                    copy(log, self.path.join(UNITY_LOG_FILE)).await?;
+                }
+                None => debug!("No Unity log written during the session"),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The sessions kept, the latest first
+    pub async fn list() -> anyhow::Result<Vec<LogSession>> {
+        let logs_dir = Self::get_logs_directory().await;
+        if !logs_dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut sessions = vec![];
+        let mut entries = read_dir(logs_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            match Self::load(entry.path()).await {
+                Ok(session) => sessions.push(session),
+                Err(e) => warn!("Ignoring the log session {:?} : {}", entry.path(), e),
+            }
+        }
+
+        sessions.sort_by(|a, b| b.info.started_at.cmp(&a.info.started_at));
+        Ok(sessions)
+    }
+
+    /// Remove the oldest sessions to keep only `retention` of them
+    pub async fn prune(retention: usize) -> anyhow::Result<()> {
+        for session in Self::list().await?.into_iter().skip(retention) {
+            debug!("Removing the log session {}", session.get_id());
+            remove_dir_all(session.path).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn load(path: PathBuf) -> anyhow::Result<Self> {
+        let content = read_to_string(path.join(SESSION_INFO_FILE)).await?;
+
+        Ok(Self {
+            path,
+            info: serde_json::from_str(&content)?,
+            unity_log_prefix: None,
+        })
+    }
+
+    async fn save_info(&self) -> anyhow::Result<()> {
+        write(
+            self.path.join(SESSION_INFO_FILE),
+            serde_json::to_string(&self.info)?,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Unity writes its log in `AppData/LocalLow/<company>/<product>/Player.log`, the latest one
+/// written since `since` is returned
+fn find_unity_log(prefix: &Path, since: SystemTime) -> Option<PathBuf> {
+    let users = std::fs::read_dir(prefix.join("drive_c").join("users")).ok()?;
+
+    users
+        .flatten()
+        .filter_map(|user| std::fs::read_dir(user.path().join("AppData").join("LocalLow")).ok())
+        .flat_map(|companies| companies.flatten())
+        .filter_map(|company| std::fs::read_dir(company.path()).ok())
+        .flat_map(|products| products.flatten())
+        .map(|product| product.path().join(UNITY_LOG_FILE))
+        .filter_map(|log| {
+            let modified = log.metadata().and_then(|m| m.modified()).ok()?;
+            (modified >= since).then_some((modified, log))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, log)| log)
+}
@@ -1,42 +1,83 @@
 use std::{
-    io::{BufRead, BufReader},
-    path::PathBuf,
-    process::{Child, Command, Stdio},
-    sync::{Arc, Mutex},
+    ffi::OsString,
+    path::{Path, PathBuf},
+    process::ExitStatus,
+    sync::Arc,
 };
 
 use downloader::progress::Reporter;
-use log::{debug, info};
+use log::{debug, info, warn};
 use tokio::{
-    fs::{create_dir_all, remove_file, File, OpenOptions},
-    io::AsyncWriteExt,
+    fs::{create_dir_all, remove_dir_all},
+    sync::mpsc,
 };
 use wincompatlib::prelude::*;
 
 use crate::{
     components::{
-        component_downloader::ComponentDownloader, dxvk_component::DXVKComponent,
-        game_component::GameComponent, wine_component::WineComponent,
+        component_downloader::ComponentDownloader,
+        dxvk_component::{DXVKComponent, DXVK_DEV, DXVK_REPO},
+        game_component::{GameComponent, VerifyReport},
+        vkd3d_component::{Vkd3dComponent, VKD3D_DEV, VKD3D_REPO},
+        wine_component::{PrefixError, WineComponent, WINE_DEV, WINE_REPO},
     },
-    game_config::GameConfig,
+    diagnostics, dxvk_config,
+    game_config::{GameConfig, RenderApi},
+    game_logs::LogSession,
     game_patcher,
-    utils::{get_game_name, get_game_name_with_executable, github_requester::GithubRequester},
+    game_process::{self, GameProcess, LogLine},
+    hooks::{self, HookEnv},
+    instance_lock::{self, InstanceLock, Operation},
+    launch_env::LaunchEnv,
+    launch_options::LaunchCommand,
+    player_prefs::PlayerPrefs,
+    prefix_manifest,
+    prefix_settings::PrefixSettings,
+    prepared_launch::PreparedLaunch,
+    runner::Runner,
+    utils::{
+        archive, get_game_name, get_game_name_with_executable, github_requester::GithubRequester,
+        winetricks,
+    },
 };
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EnvironmentVariable {
-    name: String,
-    value: String,
+    pub name: String,
+    pub value: String,
 }
 
 impl EnvironmentVariable {
-    pub fn parse(variable: &str) -> Self {
-        let split: Vec<&str> = variable.split('=').collect();
+    pub fn new(name: &str, value: &str) -> anyhow::Result<Self> {
+        if !Self::is_valid_name(name) {
+            anyhow::bail!(
+                "Invalid environment variable name '{}', only letters, digits and _ are allowed",
+                name
+            );
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            value: value.to_string(),
+        })
+    }
 
-        Self {
-            name: split[0].to_string(),
-            value: split[1].to_string(),
+    /// Parse `NAME=value`, everything after the first `=` is the value
+    pub fn parse(variable: &str) -> anyhow::Result<Self> {
+        match variable.split_once('=') {
+            Some((name, value)) => Self::new(name.trim(), value),
+            None => anyhow::bail!(
+                "The value of '{}' is missing, expected NAME=value",
+                variable
+            ),
         }
     }
+
+    pub fn is_valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
 }
 
 pub struct GameManager;
@@ -57,11 +98,37 @@ impl GameManager {
 
         let mut config = GameConfig::get_config().await;
         config.is_wine_installed = true;
+        config.components.wine = wine_component.get_github_release_tag();
         GameConfig::save_config(config).await?;
 
         Ok(wine_component)
     }
 
+    /// Create the wine prefix if it doesn't exist, everything installed in the previous prefix
+    /// is marked as not installed so it's installed again
+    pub async fn init_prefix<P>(wine: &Wine, progress: Option<Arc<P>>) -> Result<(), PrefixError>
+    where
+        P: Reporter + 'static,
+    {
+        if !WineComponent::init_prefix(wine, progress).await? {
+            return Ok(());
+        }
+
+        let mut config = GameConfig::get_config().await;
+        config.is_dxvk_installed = false;
+        config.is_vkd3d_installed = false;
+        config.is_font_installed = false;
+        config.is_dependecies_installed = false;
+        config.prefix_manifest.reset();
+        config.is_prefix_settings_applied = false;
+        config.replaced_dll_overrides.clear();
+        GameConfig::save_config(config)
+            .await
+            .map_err(PrefixError::Config)?;
+
+        Ok(())
+    }
+
     pub async fn install_dxvk<P>(
         wine: &Wine,
         config_dir: PathBuf,
@@ -78,6 +145,29 @@ impl GameManager {
 
         let mut config = GameConfig::get_config().await;
         config.is_dxvk_installed = true;
+        config.components.dxvk = dxvk_component.get_github_release_tag();
+        GameConfig::save_config(config).await?;
+
+        Ok(())
+    }
+
+    pub async fn install_vkd3d<P>(
+        wine: &Wine,
+        config_dir: PathBuf,
+        release_index: usize,
+        progress: Option<Arc<P>>,
+    ) -> anyhow::Result<()>
+    where
+        P: Reporter + 'static,
+    {
+        let mut vkd3d_component = Vkd3dComponent::from_wine(wine, config_dir);
+        vkd3d_component.set_github_release_index(release_index);
+
+        vkd3d_component.install(progress).await?;
+
+        let mut config = GameConfig::get_config().await;
+        config.is_vkd3d_installed = true;
+        config.components.vkd3d = vkd3d_component.get_github_release_tag();
         GameConfig::save_config(config).await?;
 
         Ok(())
@@ -87,7 +177,8 @@ impl GameManager {
     where
         P: Reporter + 'static,
     {
-        let max = 1;
+        let fonts = GameConfig::get_prefix_manifest().await.get_pending_fonts();
+        let max = fonts.len() as u64;
 
         if let Some(p) = &progress {
             p.setup(Some(max), "");
@@ -95,8 +186,19 @@ impl GameManager {
 
         notify_fonts_progress(0, max, &progress);
 
-        wine.install_font(Font::Arial)?;
-        notify_fonts_progress(1, max, &progress);
+        for (i, font) in fonts.iter().enumerate() {
+            match prefix_manifest::get_wincompatlib_font(font) {
+                Some(font) => wine.install_font(font)?,
+                None => winetricks::install(wine, font).await?,
+            }
+
+            // saved after each font, so a failure doesn't reinstall everything
+            let mut config = GameConfig::get_config().await;
+            config.prefix_manifest.set_font_installed(font, true);
+            GameConfig::save_config(config).await?;
+
+            notify_fonts_progress(i as u64 + 1, max, &progress);
+        }
 
         let mut config = GameConfig::get_config().await;
         config.is_font_installed = true;
@@ -106,13 +208,16 @@ impl GameManager {
     }
 
     pub async fn install_dependencies(wine: &Wine) -> anyhow::Result<()> {
-        let winetricks = Winetricks::from_wine("/bin/winetricks", wine);
-        //winetricks.install("corefonts")?;
-        let mut child = winetricks.install("vcrun2022")?;
+        let verbs = GameConfig::get_prefix_manifest().await.get_pending_verbs();
+
+        for verb in verbs {
+            info!("Installing {}...", verb);
+            winetricks::install(wine, &verb).await?;
 
-        child
-            .wait()
-            .expect("Something failed when waiting for the installation");
+            let mut config = GameConfig::get_config().await;
+            config.prefix_manifest.set_verb_installed(&verb, true);
+            GameConfig::save_config(config).await?;
+        }
 
         let mut config = GameConfig::get_config().await;
         config.is_dependecies_installed = true;
@@ -121,10 +226,161 @@ impl GameManager {
         Ok(())
     }
 
+    /// Save the new prefix settings, they are applied right away if the prefix exists, or the next
+    /// time the game is started otherwise
+    pub async fn set_prefix_settings(
+        wine: Option<&Wine>,
+        settings: PrefixSettings,
+    ) -> anyhow::Result<()> {
+        let mut config = GameConfig::get_config().await;
+        if config.runner.manages_own_prefix() && settings != PrefixSettings::default() {
+            anyhow::bail!(
+                "The {} runner manages its own prefix, the prefix settings can't be applied to it",
+                config.runner
+            );
+        }
+
+        let previous = config.prefix_settings.clone();
+
+        config.prefix_settings = settings.clone();
+        config.is_prefix_settings_applied = false;
+        GameConfig::save_config(config).await?;
+
+        if let Some(wine) = wine {
+            let mut config = GameConfig::get_config().await;
+            // the replaced overrides are saved even when it fails half way
+            let result = settings.apply(wine, Some(&previous), &mut config.replaced_dll_overrides);
+
+            config.is_prefix_settings_applied = result.is_ok();
+            GameConfig::save_config(config).await?;
+            result?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn apply_prefix_settings(wine: &Wine) -> anyhow::Result<()> {
+        let mut config = GameConfig::get_config().await;
+        if config.runner.manages_own_prefix() {
+            anyhow::bail!(
+                "The {} runner manages its own prefix, the prefix settings can't be applied to it",
+                config.runner
+            );
+        }
+
+        let result = config
+            .prefix_settings
+            .apply(wine, None, &mut config.replaced_dll_overrides);
+
+        config.is_prefix_settings_applied = result.is_ok();
+        GameConfig::save_config(config).await?;
+        result?;
+
+        Ok(())
+    }
+
+    /// Save the wine prefix used by the game to a tar.gz archive
+    pub async fn backup_prefix(output_file: PathBuf) -> anyhow::Result<()> {
+        let config = GameConfig::get_config().await;
+        let prefix = config.runner.get_prefix_path(&config.config_dir);
+        let _lock = Self::lock_prefix(&prefix).await?;
+
+        if !prefix.exists() {
+            anyhow::bail!("There's no wine prefix to backup");
+        }
+
+        info!(
+            "Saving the wine prefix {:?} to {:?}...",
+            prefix, output_file
+        );
+        archive::compress_dir(prefix, output_file).await
+    }
+
+    /// Replace the wine prefix used by the game by a backup made with
+    /// [`GameManager::backup_prefix`]
+    pub async fn restore_prefix<P>(
+        backup_file: PathBuf,
+        progress: Option<Arc<P>>,
+    ) -> anyhow::Result<()>
+    where
+        P: Reporter + 'static,
+    {
+        let config = GameConfig::get_config().await;
+        let prefix = config.runner.get_prefix_path(&config.config_dir);
+        let _lock = Self::lock_prefix(&prefix).await?;
+
+        if !backup_file.is_file() {
+            anyhow::bail!("The backup {} doesn't exist", backup_file.display());
+        }
+
+        info!(
+            "Restoring the wine prefix {:?} from {:?}...",
+            prefix, backup_file
+        );
+        archive::extract_and_replace(backup_file, prefix, progress).await
+    }
+
+    /// Delete the wine prefix, create a new one and reinstall the same versions of DXVK and
+    /// VKD3D-Proton, the fonts and the dependencies in it
+    pub async fn reset_prefix<P>(wine: &Wine, progress: Option<Arc<P>>) -> anyhow::Result<()>
+    where
+        P: Reporter + 'static,
+    {
+        let config_dir = GameConfig::get_config_directory().await;
+
+        let mut config = GameConfig::get_config().await;
+        if config.runner.get_prefix_path(&config_dir) != wine.prefix {
+            anyhow::bail!(
+                "The {} runner manages its own prefix, it can't be reset by the launcher",
+                config.runner
+            );
+        }
+        let _lock = Self::lock_prefix(&wine.prefix).await?;
+
+        let components = config.components.clone();
+        let reinstall_vkd3d = config.is_vkd3d_installed;
+
+        config.is_dxvk_installed = false;
+        config.is_vkd3d_installed = false;
+        config.is_font_installed = false;
+        config.is_dependecies_installed = false;
+        config.prefix_manifest.reset();
+        config.is_prefix_settings_applied = false;
+        config.replaced_dll_overrides.clear();
+        GameConfig::save_config(config).await?;
+
+        if wine.prefix.exists() {
+            info!("Removing the wine prefix {:?}...", wine.prefix);
+            remove_dir_all(&wine.prefix).await?;
+        }
+
+        Self::init_prefix(wine, progress.clone()).await?;
+
+        let dxvk_release =
+            get_release_index::<DXVKComponent>(DXVK_DEV, DXVK_REPO, components.dxvk).await?;
+        Self::install_dxvk(wine, config_dir.clone(), dxvk_release, progress.clone()).await?;
+
+        if reinstall_vkd3d {
+            let vkd3d_release =
+                get_release_index::<Vkd3dComponent>(VKD3D_DEV, VKD3D_REPO, components.vkd3d)
+                    .await?;
+            Self::install_vkd3d(wine, config_dir, vkd3d_release, progress.clone()).await?;
+        }
+
+        Self::install_font(wine, progress).await?;
+        Self::install_dependencies(wine).await?;
+        Self::apply_prefix_settings(wine).await?;
+
+        info!("Wine prefix reset");
+
+        Ok(())
+    }
+
     pub async fn install_game<P>(game_dir: PathBuf, progress: Arc<P>) -> anyhow::Result<()>
     where
         P: Reporter + 'static,
     {
+        let _lock = Self::lock(Operation::Install).await?;
         let _ = create_dir_all(game_dir.clone()).await;
 
         let game_component = GameComponent::new(game_dir);
@@ -140,6 +396,7 @@ impl GameManager {
     // this function just pass is_game_installed and is_game_patched to false,
     // so the launcher on the next iteration download the new file and delete the old one with the check process in the installation process
     pub async fn update_game() -> anyhow::Result<()> {
+        let _lock = Self::lock(Operation::Update).await?;
         let mut config = GameConfig::get_config().await;
         config.is_game_installed = false;
         config.is_game_patched = false;
@@ -148,204 +405,322 @@ impl GameManager {
         Ok(())
     }
 
+    /// Check the game files by their size, without downloading anything
+    pub async fn verify_game(game_dir: PathBuf) -> anyhow::Result<VerifyReport> {
+        let _lock = Self::lock(Operation::Verify).await?;
+        GameComponent::new(game_dir).verify().await
+    }
+
+    /// Read the PlayerPrefs saved by the game in the wine prefix
+    pub async fn get_player_prefs(game_dir: PathBuf) -> anyhow::Result<PlayerPrefs> {
+        let config = GameConfig::get_config().await;
+        PlayerPrefs::load(
+            &game_dir,
+            &config.runner.get_prefix_path(&config.config_dir),
+        )
+        .await
+    }
+
+    /// Edit the PlayerPrefs with `edit` and save them. The game writes its PlayerPrefs when it
+    /// exits and wine writes the whole registry when its wineserver exits, so both need to be
+    /// closed before the registry is read
+    pub async fn edit_player_prefs<T>(
+        game_dir: PathBuf,
+        edit: impl FnOnce(&mut PlayerPrefs) -> anyhow::Result<T>,
+    ) -> anyhow::Result<T> {
+        let _lock = Self::lock(Operation::EditRegistry).await?;
+
+        let config = GameConfig::get_config().await;
+        let prefix = config.runner.get_prefix_path(&config.config_dir);
+        Self::stop_wineserver(&config.runner, &config.config_dir, &prefix).await?;
+        if let Some(pid) = instance_lock::find_wineserver(&prefix) {
+            anyhow::bail!(
+                "The wineserver of the prefix is still running (pid {}), close wine before {}",
+                pid,
+                Operation::EditRegistry
+            );
+        }
+
+        let mut prefs = Self::get_player_prefs(game_dir).await?;
+        let result = edit(&mut prefs)?;
+        prefs.save().await?;
+
+        Ok(result)
+    }
+
+    /// Remove the resolution, the window mode and the quality saved by the game, return how many
+    /// values were removed
+    pub async fn reset_graphics_settings(game_dir: PathBuf) -> anyhow::Result<usize> {
+        Self::edit_player_prefs(game_dir, |prefs| Ok(prefs.reset_graphics())).await
+    }
+
+    /// Remove the shader caches of DXVK and VKD3D-Proton, return how many files were removed
+    pub async fn clear_shader_cache() -> anyhow::Result<usize> {
+        let _lock = Self::lock(Operation::ClearShaderCache).await?;
+
+        let config = GameConfig::get_config().await;
+        dxvk_config::clear_shader_cache(&config.config_dir, config.game_dir.as_deref()).await
+    }
+
+    /// Refuse to start `operation` if another one is in progress or if the game is running
+    async fn lock(operation: Operation) -> anyhow::Result<InstanceLock> {
+        let lock = InstanceLock::acquire(operation).await?;
+
+        let config = GameConfig::get_config().await;
+        if let Some(pid) =
+            instance_lock::find_running_game(&config.runner.get_prefix_path(&config.config_dir))
+        {
+            anyhow::bail!(
+                "The game is already running (pid {}), close it before {}",
+                pid,
+                operation
+            );
+        }
+
+        Ok(lock)
+    }
+
+    /// Like [`GameManager::lock`], and refuse if wine is still running in `prefix`
+    async fn lock_prefix(prefix: &Path) -> anyhow::Result<InstanceLock> {
+        let lock = Self::lock(Operation::Prefix).await?;
+
+        if let Some(pid) = instance_lock::find_wineserver(prefix) {
+            anyhow::bail!(
+                "The wineserver of the prefix is still running (pid {}), close wine before {}",
+                pid,
+                Operation::Prefix
+            );
+        }
+
+        Ok(lock)
+    }
+
+    /// Write a tar.gz with the config, the versions, the system and the latest logs, to attach to
+    /// a bug report, the wine version is only added if `wine` is given
+    pub async fn export_diagnostics(wine: Option<&Wine>, output: PathBuf) -> anyhow::Result<()> {
+        diagnostics::export(wine, output).await
+    }
+
     pub async fn patch_game(game_dir: PathBuf) -> anyhow::Result<()> {
         game_patcher::patch_game(game_dir).await?;
 
         Ok(())
     }
 
+    /// Start the game and wait for it to exit, its output is kept in a new log session
     pub async fn start_game(
         wine: &Wine,
         game_dir: PathBuf,
         options: Option<String>,
         env_variables: Vec<EnvironmentVariable>,
         show_logs: bool,
+    ) -> anyhow::Result<ExitStatus> {
+        // listening before the game is started, to show its first lines too
+        let output = show_logs.then(|| {
+            let (sender, mut receiver) = mpsc::unbounded_channel::<LogLine>();
+            tokio::task::spawn(async move {
+                while let Some(line) = receiver.recv().await {
+                    info!("[wine] > {}", line.line);
+                }
+            });
+            sender
+        });
+
+        let process = Self::launch_game(wine, game_dir, options, env_variables, output).await?;
+        process.wait().await
+    }
+
+    /// Stop the game, then the processes left in its prefix, return false if the game was not
+    /// running
+    pub async fn stop_game() -> anyhow::Result<bool> {
+        let config = GameConfig::get_config().await;
+        let prefix = config.runner.get_prefix_path(&config.config_dir);
+
+        let was_running = game_process::stop(&prefix).await?;
+
+        Self::stop_wineserver(&config.runner, &config.config_dir, &prefix).await?;
+
+        Ok(was_running)
+    }
+
+    // stop the processes left in the prefix and wait for the wineserver to write the registry
+    async fn stop_wineserver(
+        runner: &Runner,
+        config_dir: &Path,
+        prefix: &Path,
     ) -> anyhow::Result<()> {
-        let wine_version = wine.version()?;
-        let binary_path = game_dir
-            .join(get_game_name())
-            .join(get_game_name_with_executable());
+        let Some(wineserver) = runner.get_wineserver(config_dir) else {
+            debug!("The wineserver of the {} runner is unknown", runner);
+            return Ok(());
+        };
 
-        debug!("wine version : {:?}", wine_version);
+        for arg in ["-k", "-w"] {
+            let output = tokio::process::Command::new(&wineserver)
+                .arg(arg)
+                .env("WINEPREFIX", prefix)
+                .output()
+                .await?;
 
-        let mut child = if let Some(custom_command) = options {
-            Self::run(wine, binary_path, Some(custom_command), env_variables).await?
-        } else {
-            if let Some(custom_command) = GameConfig::get_launch_options().await.unwrap() {
-                Self::run(wine, binary_path, Some(custom_command), env_variables).await?
-            } else {
-                Self::run(wine, binary_path, None, env_variables).await?
+            // it fails when there's no wineserver running in the prefix
+            if !output.status.success() {
+                debug!("wineserver {} failed : {}", arg, output.status);
             }
-        }?;
+        }
 
-        let log_stdout = Arc::new(Mutex::new(None));
-        let log_stderr = Arc::new(Mutex::new(None));
+        Ok(())
+    }
 
-        if show_logs {
-            let stderr = child.stderr.take().unwrap();
-            let stdout = child.stdout.take().unwrap();
+    /// Start the game without waiting for it, to subscribe to its output, `output` receives all
+    /// the lines written by the game
+    pub async fn launch_game(
+        wine: &Wine,
+        game_dir: PathBuf,
+        options: Option<String>,
+        env_variables: Vec<EnvironmentVariable>,
+        output: Option<mpsc::UnboundedSender<LogLine>>,
+    ) -> anyhow::Result<GameProcess> {
+        let lock = Self::lock(Operation::Launch).await?;
 
-            let log_stdout_ref = log_stdout.clone();
-            let log_stderr_ref = log_stderr.clone();
+        let wine_version = wine.version()?;
+        debug!("wine version : {:?}", wine_version);
 
-            tokio::task::spawn(async move {
-                let bufread = BufReader::new(stdout);
-                let mut stdout_save = String::new();
-                let _: Vec<_> = bufread
-                    .lines()
-                    .inspect(|s| {
-                        if let Ok(str) = s {
-                            info!("[wine] > {}", str);
-                            stdout_save.push_str(str);
-                        }
-                    })
-                    .collect();
-                *log_stdout_ref.lock().unwrap() = Some(stdout_save);
-            });
-            tokio::task::spawn(async move {
-                let bufread = BufReader::new(stderr);
-                let mut stderr_save = String::new();
-                let _: Vec<_> = bufread
-                    .lines()
-                    .inspect(|s| {
-                        if let Ok(str) = s {
-                            info!("[wine] > {}", str);
-                            stderr_save.push_str(str);
-                        }
-                    })
-                    .collect();
-                *log_stderr_ref.lock().unwrap() = Some(stderr_save);
-            });
+        let prepared = Self::prepare_launch(wine, game_dir.clone(), options, env_variables).await?;
+
+        let config = GameConfig::get_config().await;
+        if config.runner.manages_own_prefix() {
+            if config.prefix_settings != PrefixSettings::default() {
+                warn!(
+                    "The prefix settings are not used by the {} runner, it manages its own prefix",
+                    config.runner
+                );
+            }
+        } else if !config.is_prefix_settings_applied {
+            // the settings changed before the prefix was created
+            Self::apply_prefix_settings(wine).await?;
         }
 
-        let output = child
-            .wait_with_output()
-            .expect("Failed to wait for the process");
+        let config = GameConfig::get_config().await;
+        let prefix = config.runner.get_prefix_path(&config.config_dir);
+        let mut hook_env = HookEnv {
+            game_dir,
+            prefix: prefix.clone(),
+            log_dir: None,
+            exit_code: None,
+        };
 
-        let log_file_path = GameConfig::get_config_directory().await.join("game.log");
-        if log_file_path.exists() {
-            remove_file(log_file_path.clone())
+        if let Some(command) = &config.hooks.pre_launch {
+            hooks::run_hook("pre-launch", command, &hook_env)
                 .await
-                .expect("Failed to remove old log file");
+                .map_err(|e| anyhow::anyhow!("{}, the game was not started", e))?;
         }
-        File::create(log_file_path.clone())
-            .await
-            .expect("Failed to create build file");
 
-        let mut log_file = OpenOptions::new()
-            .write(true)
-            .append(true)
-            .open(log_file_path)
-            .await
-            .expect("Failed to open the log file");
-
-        log_file
-            .write_all("--- stdout ---\n".as_bytes())
-            .await
-            .expect("Failed to write the output to the log file");
-
-        let log_stdout_value = log_stdout.lock().unwrap();
-        let v;
-        let to_write = if log_stdout_value.is_some() {
-            v = log_stdout_value.clone().unwrap();
-            v.as_bytes()
-        } else {
-            &output.stdout
+        let spawned = match LogSession::create(&prefix).await {
+            Ok(session) => {
+                hook_env.log_dir = Some(session.path.clone());
+                GameProcess::spawn(&prepared, session, lock, output).await
+            }
+            Err(e) => Err(e),
         };
 
-        log_file
-            .write_all(to_write)
-            .await
-            .expect("Failed to write the output to the log file");
-
-        log_file
-            .write_all("--- stderr ---\n".as_bytes())
-            .await
-            .expect("Failed to write the output to the log file");
-
-        let log_stderr_value = log_stderr.lock().unwrap();
-        let v;
-        let to_write = if log_stderr_value.is_some() {
-            debug!("test");
-            v = log_stderr_value.clone().unwrap();
-            v.as_bytes()
-        } else {
-            &output.stderr
+        let Some(command) = config.hooks.post_exit else {
+            return spawned;
         };
 
-        log_file
-            .write_all(to_write)
-            .await
-            .expect("Failed to write the output to the log file");
-
-        Ok(())
+        match spawned {
+            Ok(mut process) => {
+                process.set_post_exit_hook(command, hook_env);
+                Ok(process)
+            }
+            // to revert what the pre-launch hook did
+            Err(e) => {
+                if let Err(e) = hooks::run_hook("post-exit", &command, &hook_env).await {
+                    warn!("{}", e);
+                }
+                Err(e)
+            }
+        }
     }
 
-    async fn run(
+    /// Resolve the command, the working directory and the environment of the game without
+    /// starting it
+    pub async fn prepare_launch(
         wine: &Wine,
-        binary_path: PathBuf,
-        custom_command: Option<String>,
+        game_dir: PathBuf,
+        options: Option<String>,
         env_variables: Vec<EnvironmentVariable>,
-    ) -> anyhow::Result<Result<Child, std::io::Error>> {
-        let mut command: Vec<&str> = vec![];
+    ) -> anyhow::Result<PreparedLaunch> {
+        let config_dir = GameConfig::get_config_directory().await;
+        let config = GameConfig::get_config().await;
+
+        if config.render_api == RenderApi::D3D12 && !config.is_vkd3d_installed {
+            anyhow::bail!(
+                "DirectX 12 is selected but VKD3D-Proton is not installed, install it or switch back to DirectX 11"
+            );
+        }
 
-        let wine_path = GameConfig::get_config_directory()
-            .await
-            .join("wine")
-            .join("bin")
-            .join("wine");
-        let mut wine_path = wine_path.to_str().unwrap();
+        // the version is not known by a wine that can't be started, the launch fails later
+        let wine_version = wine.version().ok();
+        config.runner_settings.check(
+            &config.runner,
+            wine_version.as_ref().and_then(|v| v.to_str()),
+        )?;
 
-        if let Some(distro) = whatadistro::identify() {
-            if distro.is_similar("nixos") {
-                wine_path = "wine";
+        // written here so the command can also be run from a terminal
+        dxvk_config::write_config(&config_dir, &config.dxvk).await?;
 
-                info!("Nixos detected, using system Wine...");
-            };
-        };
+        let working_dir = game_dir.join(get_game_name());
+        let binary_path = working_dir.join(get_game_name_with_executable());
 
-        //command.push(wine.python.to_str().unwrap());
-        command.push(wine_path);
-        //command.push("run");
-        command.push(binary_path.to_str().unwrap());
-
-        let launch_option;
-        if custom_command.is_some() {
-            launch_option = custom_command.unwrap();
-            debug!("Starting game with --options -> {}", launch_option.clone());
-            let tokens: Vec<&str> = launch_option.split_whitespace().collect();
-
-            // position of the %command%
-            let index = if let Some(v) = tokens.iter().position(|&s| s == "%command%") {
-                v
-            } else {
-                anyhow::bail!("You forget to put %command% in your custom launch command");
-            };
-
-            let start_command = tokens[0..index].to_vec();
-            let mut end_command = tokens[(index + 1)..tokens.len()].to_vec();
-
-            start_command.iter().rev().for_each(|str| {
-                command.insert(0, str);
-            });
-            command.append(&mut end_command);
-        } else {
-            debug!("Starting game without --options");
+        debug!("Starting game with the {} runner", config.runner);
+        let mut game_command: Vec<OsString> = config.runner.get_command(&config_dir, &binary_path);
+
+        game_command.extend(
+            config
+                .unity
+                .get_args(config.render_api)
+                .into_iter()
+                .map(OsString::from),
+        );
+
+        // the options passed for this launch replace the raw options of the config
+        let mut launch_options = config.launch_options.clone();
+        if let Some(raw) = options {
+            launch_options.raw = Some(raw);
         }
 
-        debug!("Command preview -> {}", command.join(" "));
-        debug!(
-            "Command envs -> {:?}",
-            wine.get_envs()["WINEPREFIX"].clone()
+        debug!("Launch options -> {:?}", launch_options);
+        let LaunchCommand { command, env_vars } = launch_options.build(game_command)?;
+
+        let env = LaunchEnv::build(
+            wine,
+            &config.runner,
+            &config_dir,
+            &config,
+            env_vars,
+            env_variables,
         );
 
-        Ok(Command::new(command[0])
-            .args(&command[1..command.len()])
-            .env("WINE_PREFIX", wine.get_envs()["WINEPREFIX"].clone())
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn())
+        let prepared = PreparedLaunch {
+            command,
+            working_dir,
+            env,
+        };
+        debug!("Command preview -> {}", prepared.get_command_line());
+        debug!("Launch environment :\n{}", prepared.env.dump());
+
+        Ok(prepared)
+    }
+}
+
+// the latest release is used if the version installed before is unknown
+async fn get_release_index<T: GithubRequester>(
+    user: &str,
+    repo_name: &str,
+    tag_name: Option<String>,
+) -> anyhow::Result<usize> {
+    match tag_name {
+        Some(tag) => T::get_github_release_index(user, repo_name, &tag).await,
+        None => Ok(0),
     }
 }
 
@@ -358,3 +733,46 @@ where
         p.progress(nbr);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_env_variables() {
+        assert_eq!(
+            EnvironmentVariable::parse("DXVK_HUD=fps").unwrap(),
+            EnvironmentVariable {
+                name: "DXVK_HUD".to_string(),
+                value: "fps".to_string(),
+            }
+        );
+        // only the first = separates the name
+        assert_eq!(
+            EnvironmentVariable::parse("WINEDLLOVERRIDES=d3d11=n;dxgi=n")
+                .unwrap()
+                .value,
+            "d3d11=n;dxgi=n"
+        );
+        // the spaces around the name are removed, the value is kept as it is
+        assert_eq!(
+            EnvironmentVariable::parse(" FOO = bar ").unwrap(),
+            EnvironmentVariable {
+                name: "FOO".to_string(),
+                value: " bar ".to_string(),
+            }
+        );
+        assert_eq!(EnvironmentVariable::parse("FOO=").unwrap().value, "");
+    }
+
+    #[test]
+    fn reject_invalid_env_variables() {
+        assert!(EnvironmentVariable::parse("FOO").is_err());
+        assert!(EnvironmentVariable::parse("=bar").is_err());
+        assert!(EnvironmentVariable::parse("1FOO=bar").is_err());
+        assert!(EnvironmentVariable::parse("FO O=bar").is_err());
+        assert!(EnvironmentVariable::parse("FOO-BAR=bar").is_err());
+        assert!(EnvironmentVariable::new("", "bar").is_err());
+        assert!(EnvironmentVariable::is_valid_name("_FOO1"));
+    }
+}
@@ -0,0 +1,315 @@
+use std::{
+    fmt::Display,
+    path::Path,
+    process::ExitStatus,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+use log::{debug, error, warn};
+use tokio::{
+    fs::File,
+    io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader},
+    process::{Child, Command},
+    sync::{broadcast, mpsc},
+    task::JoinHandle,
+};
+
+use crate::{
+    game_logs::LogSession,
+    hooks::{self, HookEnv},
+    instance_lock::{find_running_game, InstanceLock},
+    playtime::PlaytimeHistory,
+    prepared_launch::PreparedLaunch,
+};
+
+// the time given to the game to exit after SIGTERM, before it's killed
+static STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+// the time given to the kernel to remove the game after SIGKILL
+static KILL_TIMEOUT: Duration = Duration::from_secs(5);
+
+// the time given to the readers to reach the end of the output after the game exited
+static OUTPUT_TIMEOUT: Duration = Duration::from_secs(2);
+
+// the lines sent while a subscriber is lagging this much behind are lost for it
+static SUBSCRIBERS_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+impl Display for LogStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogStream::Stdout => write!(f, "stdout"),
+            LogStream::Stderr => write!(f, "stderr"),
+        }
+    }
+}
+
+/// A line written by the game process
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLine {
+    pub time: DateTime<Local>,
+    pub stream: LogStream,
+    pub line: String,
+}
+
+impl Display for LogLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] [{}] {}",
+            self.time.format("%Y-%m-%d %H:%M:%S%.3f"),
+            self.stream,
+            self.line
+        )
+    }
+}
+
+/// Supervise the game process, its output is written to the log of the session as it arrives and
+/// is sent to the subscribers
+pub struct GameProcess {
+    child: Child,
+    session: LogSession,
+    // released when the game exits
+    _lock: InstanceLock,
+    post_exit_hook: Option<(String, HookEnv)>,
+    lines: broadcast::Sender<LogLine>,
+    readers: Vec<JoinHandle<()>>,
+    writer: JoinHandle<()>,
+}
+
+impl GameProcess {
+    /// `output` receives all the lines written by the game, including the first ones that are
+    /// sent before [`GameProcess::subscribe`] can be called
+    pub async fn spawn(
+        prepared: &PreparedLaunch,
+        session: LogSession,
+        lock: InstanceLock,
+        output: Option<mpsc::UnboundedSender<LogLine>>,
+    ) -> anyhow::Result<Self> {
+        let mut log_file = File::create(session.get_game_log_path()).await?;
+        let mut child = prepared.spawn()?;
+        debug!("Game process started with the pid {:?}", child.id());
+
+        let (lines, _) = broadcast::channel(SUBSCRIBERS_CAPACITY);
+        let (sender, mut receiver) = mpsc::unbounded_channel::<LogLine>();
+
+        // both streams are going through the same channel, so the lines stay in order
+        let readers = [
+            read_lines(child.stdout.take(), LogStream::Stdout, sender.clone()),
+            read_lines(child.stderr.take(), LogStream::Stderr, sender),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        let subscribers = lines.clone();
+        let writer = tokio::task::spawn(async move {
+            while let Some(line) = receiver.recv().await {
+                if let Err(e) = log_file.write_all(format!("{}\n", line).as_bytes()).await {
+                    error!(
+                        "Failed to write the output of the game to the log file : {}",
+                        e
+                    );
+                }
+
+                if let Some(output) = &output {
+                    let _ = output.send(line.clone());
+                }
+
+                // there's no error if nobody is listening
+                let _ = subscribers.send(line);
+            }
+
+            if let Err(e) = log_file.flush().await {
+                error!(
+                    "Failed to write the output of the game to the log file : {}",
+                    e
+                );
+            }
+        });
+
+        Ok(Self {
+            child,
+            session,
+            _lock: lock,
+            post_exit_hook: None,
+            lines,
+            readers,
+            writer,
+        })
+    }
+
+    /// Receive the lines written by the game from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<LogLine> {
+        self.lines.subscribe()
+    }
+
+    pub fn id(&self) -> Option<u32> {
+        self.child.id()
+    }
+
+    /// Run `command` when the game exits, the exit code is added to `env`
+    pub fn set_post_exit_hook(&mut self, command: String, env: HookEnv) {
+        self.post_exit_hook = Some((command, env));
+    }
+
+    pub fn get_session(&self) -> &LogSession {
+        &self.session
+    }
+
+    /// Wait for the game to exit and for its output to be written to the log of the session
+    pub async fn wait(mut self) -> anyhow::Result<ExitStatus> {
+        let status = self.child.wait().await?;
+        let ended_at = Local::now();
+        debug!("Game process exited with {}", status);
+
+        // the readers stop at the end of the streams, then the writer when they're dropped, but
+        // the children of wine can keep the streams open after the game exited
+        match tokio::time::timeout(OUTPUT_TIMEOUT, &mut self.writer).await {
+            Ok(result) => result?,
+            Err(_) => {
+                debug!("The output of the game is still open, it's not read anymore");
+                for reader in &self.readers {
+                    reader.abort();
+                }
+                (&mut self.writer).await?;
+            }
+        }
+
+        if let Err(e) = self.session.finish(&status, ended_at).await {
+            warn!("Failed to save the logs of the session : {}", e);
+        }
+
+        if let Err(e) = PlaytimeHistory::add_session(self.session.info.clone()).await {
+            warn!("Failed to save the playtime of the session : {}", e);
+        }
+
+        if let Some((command, mut env)) = self.post_exit_hook.take() {
+            env.exit_code = status.code();
+            if let Err(e) = hooks::run_hook("post-exit", &command, &env).await {
+                warn!("{}", e);
+            }
+        }
+
+        Ok(status)
+    }
+}
+
+fn read_lines(
+    stream: Option<impl AsyncRead + Unpin + Send + 'static>,
+    kind: LogStream,
+    sender: mpsc::UnboundedSender<LogLine>,
+) -> Option<JoinHandle<()>> {
+    let stream = stream?;
+
+    Some(tokio::task::spawn(async move {
+        let mut lines = BufReader::new(stream).lines();
+
+        loop {
+            match lines.next_line().await {
+                Ok(Some(line)) => {
+                    let _ = sender.send(LogLine {
+                        time: Local::now(),
+                        stream: kind,
+                        line,
+                    });
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    error!("Failed to read the {} of the game : {}", kind, e);
+                    break;
+                }
+            }
+        }
+    }))
+}
+
+/// Stop the game running in `prefix` with SIGTERM, then with SIGKILL if it's still running after a
+/// while, return false if the game was not running
+pub async fn stop(prefix: &Path) -> anyhow::Result<bool> {
+    let Some(pid) = find_running_game(prefix) else {
+        return Ok(false);
+    };
+
+    // the game is started in its own process group, so the wrappers and wine are stopped with it,
+    // but a game started from a terminal can share the group of the launcher
+    let target = match get_process_group(pid) {
+        Some(group) if Some(group) != get_process_group(std::process::id()) => {
+            format!("-{}", group)
+        }
+        _ => pid.to_string(),
+    };
+
+    debug!("Stopping the game ({})", target);
+    send_signal("TERM", &target).await?;
+
+    let deadline = Instant::now() + STOP_TIMEOUT;
+    while find_running_game(prefix).is_some() {
+        if Instant::now() >= deadline {
+            warn!("The game is still running, killing it");
+            send_signal("KILL", &target).await?;
+
+            // the wineserver is stopped after, it needs the game to be gone
+            let deadline = Instant::now() + KILL_TIMEOUT;
+            while find_running_game(prefix).is_some() {
+                if Instant::now() >= deadline {
+                    anyhow::bail!("The game is still running after it was killed");
+                }
+
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            break;
+        }
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+
+    Ok(true)
+}
+
+// the game can exit between the checks, it's not an error
+async fn send_signal(signal: &str, target: &str) -> anyhow::Result<()> {
+    let output = Command::new("kill")
+        .args([&format!("-{}", signal), "--", target])
+        // the error is checked below
+        .env("LC_ALL", "C")
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        if error.contains("No such process") {
+            debug!("{} already exited", target);
+            return Ok(());
+        }
+
+        anyhow::bail!(
+            "Failed to send SIG{} to {} : {}",
+            signal,
+            target,
+            error.trim()
+        );
+    }
+
+    Ok(())
+}
+
+fn get_process_group(pid: u32) -> Option<u32> {
+    let stat =
+        std::fs::read_to_string(Path::new("/proc").join(pid.to_string()).join("stat")).ok()?;
+
+    // the name of the process is between parentheses and can contain spaces, the group comes
+    // after the state and the parent pid
+    stat.rsplit_once(')')?
+        .1
+        .split_whitespace()
+        .nth(2)?
+        .parse()
+        .ok()
+}
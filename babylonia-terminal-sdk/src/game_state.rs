@@ -1,8 +1,12 @@
-use crate::{game_config::GameConfig, utils::kuro_prod_api::GameInfo};
+use crate::{
+    components::wine_component::WineComponent, game_config::GameConfig,
+    utils::kuro_prod_api::GameInfo,
+};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum GameState {
     WineNotInstalled,
+    PrefixNotInitialized,
     DXVKNotInstalled,
     FontNotInstalled,
     DependecieNotInstalled,
@@ -20,15 +24,21 @@ impl GameState {
             return Ok(GameState::WineNotInstalled);
         }
 
+        if !WineComponent::is_prefix_initialized(&GameConfig::get_prefix_directory().await) {
+            return Ok(GameState::PrefixNotInitialized);
+        }
+
         if !config.is_dxvk_installed {
             return Ok(GameState::DXVKNotInstalled);
         }
 
-        if !config.is_font_installed {
+        if !config.is_font_installed || !config.prefix_manifest.get_pending_fonts().is_empty() {
             return Ok(GameState::FontNotInstalled);
         }
 
-        if !config.is_dependecies_installed {
+        if !config.is_dependecies_installed
+            || !config.prefix_manifest.get_pending_verbs().is_empty()
+        {
             return Ok(GameState::DependecieNotInstalled);
         }
 
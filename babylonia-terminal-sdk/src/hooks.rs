@@ -0,0 +1,89 @@
+use std::{path::PathBuf, process::Stdio};
+
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, BufReader},
+    process::Command,
+};
+
+/// Commands run with `sh -c` around the game, like `powerprofilesctl set performance`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaunchHooks {
+    /// The game is not started if it fails
+    pub pre_launch: Option<String>,
+    pub post_exit: Option<String>,
+}
+
+/// What the hooks know about the launch, passed with the `BT_*` variables
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookEnv {
+    pub game_dir: PathBuf,
+    pub prefix: PathBuf,
+    /// Only known once the game is started
+    pub log_dir: Option<PathBuf>,
+    /// Not set before the launch, or if the game was killed by a signal
+    pub exit_code: Option<i32>,
+}
+
+impl HookEnv {
+    fn get_vars(&self) -> Vec<(&'static str, String)> {
+        let mut vars = vec![
+            ("BT_GAME_DIR", self.game_dir.to_string_lossy().to_string()),
+            ("BT_PREFIX", self.prefix.to_string_lossy().to_string()),
+        ];
+
+        if let Some(log_dir) = &self.log_dir {
+            vars.push(("BT_LOG_DIR", log_dir.to_string_lossy().to_string()));
+        }
+
+        if let Some(exit_code) = self.exit_code {
+            vars.push(("BT_EXIT_CODE", exit_code.to_string()));
+        }
+
+        vars
+    }
+}
+
+/// Wait for the command to exit, its output is logged without waiting for the end of the streams
+/// since they can be kept open by the programs it started in the background
+pub async fn run_hook(name: &str, command: &str, env: &HookEnv) -> anyhow::Result<()> {
+    debug!("Running the {} hook : {}", name, command);
+
+    let mut child = Command::new("sh")
+        .args(["-c", command])
+        .current_dir(&env.game_dir)
+        .envs(env.get_vars())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to run the {} hook : {}", name, e))?;
+
+    log_lines(child.stdout.take(), format!("[{} hook] >", name));
+    log_lines(child.stderr.take(), format!("[{} hook] (stderr) >", name));
+
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to run the {} hook : {}", name, e))?;
+
+    if !status.success() {
+        anyhow::bail!("The {} hook failed ({})", name, status);
+    }
+
+    Ok(())
+}
+
+fn log_lines(stream: Option<impl AsyncRead + Unpin + Send + 'static>, prefix: String) {
+    let Some(stream) = stream else {
+        return;
+    };
+
+    tokio::task::spawn(async move {
+        let mut lines = BufReader::new(stream).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            info!("{} {}", prefix, line);
+        }
+    });
+}
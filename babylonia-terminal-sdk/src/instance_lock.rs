@@ -0,0 +1,227 @@
+use std::{
+    fmt::Display,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use tokio::fs::{hard_link, read_to_string, remove_file, write};
+
+use crate::{game_config::GameConfig, utils::get_game_name_with_executable};
+
+static LOCK_FILE: &str = "babylonia-terminal.lock";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Operation {
+    Launch,
+    Install,
+    Update,
+    Verify,
+    EditRegistry,
+    ClearShaderCache,
+    Prefix,
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operation::Launch => write!(f, "running the game"),
+            Operation::Install => write!(f, "installing the game"),
+            Operation::Update => write!(f, "updating the game"),
+            Operation::Verify => write!(f, "verifying the game files"),
+            Operation::EditRegistry => write!(f, "editing the registry of the prefix"),
+            Operation::ClearShaderCache => write!(f, "clearing the shader cache"),
+            Operation::Prefix => write!(f, "managing the wine prefix"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockInfo {
+    pub pid: u32,
+    pub operation: Operation,
+}
+
+/// Prevent two launchers from starting the game or writing the game files at the same time, the
+/// lock is released when it's dropped
+#[derive(Debug)]
+pub struct InstanceLock {
+    path: PathBuf,
+    pub info: LockInfo,
+}
+
+impl InstanceLock {
+    pub async fn acquire(operation: Operation) -> anyhow::Result<Self> {
+        let path = GameConfig::get_config_directory().await.join(LOCK_FILE);
+        let info = LockInfo {
+            pid: std::process::id(),
+            operation,
+        };
+
+        // the lock is written to a temporary file first and linked in place, so the other
+        // launchers never read a lock which is not complete
+        let temp_path = path.with_extension(format!("lock.{}", info.pid));
+        write(&temp_path, serde_json::to_string(&info)?).await?;
+
+        let result = Self::link(&temp_path, &path, operation).await;
+        if let Err(e) = remove_file(&temp_path).await {
+            warn!("Failed to remove {:?} : {}", temp_path, e);
+        }
+
+        result?;
+        debug!("Lock acquired for {}", operation);
+
+        Ok(Self { path, info })
+    }
+
+    async fn link(temp_path: &Path, path: &Path, operation: Operation) -> anyhow::Result<()> {
+        // a second try is made if the lock was left by a launcher which is not running anymore
+        for _ in 0..2 {
+            match hard_link(temp_path, path).await {
+                Ok(()) => return Ok(()),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    let Some(current) = read_lock(path).await? else {
+                        // released in the meantime
+                        continue;
+                    };
+
+                    if is_process_running(current.pid) {
+                        anyhow::bail!(
+                            "The launcher is already {} (pid {}), wait for it to finish",
+                            current.operation,
+                            current.pid
+                        );
+                    }
+
+                    // another launcher could have replaced the stale lock in the meantime
+                    if read_lock(path).await? != Some(current) {
+                        continue;
+                    }
+
+                    debug!("Removing the lock of a launcher which is not running anymore");
+                    if let Err(e) = remove_file(path).await {
+                        if e.kind() != ErrorKind::NotFound {
+                            return Err(e.into());
+                        }
+                    }
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        anyhow::bail!("Failed to lock {:?} for {}", path, operation)
+    }
+
+    /// The operation in progress, `None` if there's no lock or if it was left by a launcher which
+    /// is not running anymore
+    pub async fn get_current() -> Option<LockInfo> {
+        let path = GameConfig::get_config_directory().await.join(LOCK_FILE);
+        let info = read_lock(&path).await.ok()??;
+
+        is_process_running(info.pid).then_some(info)
+    }
+}
+
+/// `None` if there's no lock, a lock which can't be read is an error so it's never taken as stale
+async fn read_lock(path: &Path) -> anyhow::Result<Option<LockInfo>> {
+    let content = match read_to_string(path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    match serde_json::from_str::<LockInfo>(&content) {
+        Ok(info) => Ok(Some(info)),
+        Err(e) => anyhow::bail!(
+            "The lock {:?} can't be read ({}), delete it if no other launcher is running",
+            path,
+            e
+        ),
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            warn!("Failed to release the lock {:?} : {}", self.path, e);
+        }
+    }
+}
+
+/// Search a running game in the wine prefix, even if it wasn't started by the launcher, return its
+/// pid
+pub fn find_running_game(prefix: &Path) -> Option<u32> {
+    let executable = get_game_name_with_executable().to_lowercase();
+
+    get_pids()?.find(|pid| {
+        let proc_dir = Path::new("/proc").join(pid.to_string());
+
+        let Ok(cmdline) = std::fs::read(proc_dir.join("cmdline")) else {
+            return false;
+        };
+
+        // wine shows the windows path of the executable, with \ as separator
+        let is_game = cmdline
+            .split(|c| *c == 0)
+            .filter_map(|arg| std::str::from_utf8(arg).ok())
+            .filter_map(|arg| arg.rsplit(['/', '\\']).next())
+            .any(|name| name.to_lowercase() == executable);
+
+        is_game && is_in_prefix(&proc_dir, prefix)
+    })
+}
+
+/// Search the wineserver of the wine prefix, return its pid
+pub fn find_wineserver(prefix: &Path) -> Option<u32> {
+    get_pids()?.find(|pid| {
+        let proc_dir = Path::new("/proc").join(pid.to_string());
+
+        // some distributions name it wineserver64
+        let is_wineserver = std::fs::read_to_string(proc_dir.join("comm"))
+            .is_ok_and(|name| name.trim().starts_with("wineserver"));
+
+        is_wineserver && is_in_prefix(&proc_dir, prefix)
+    })
+}
+
+fn get_pids() -> Option<impl Iterator<Item = u32>> {
+    Some(
+        std::fs::read_dir("/proc")
+            .ok()?
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok()),
+    )
+}
+
+// the environment of the processes of the other users can't be read, they are ignored
+fn is_in_prefix(proc_dir: &Path, prefix: &Path) -> bool {
+    let Ok(environ) = std::fs::read(proc_dir.join("environ")) else {
+        return false;
+    };
+
+    environ
+        .split(|c| *c == 0)
+        .filter_map(|var| std::str::from_utf8(var).ok())
+        .filter_map(|var| var.strip_prefix("WINEPREFIX="))
+        .any(|value| Path::new(value.trim_end_matches('/')) == prefix)
+}
+
+// the pid can be reused by another program after the launcher exited, so the executable is
+// compared too, by name because the path of an AppImage changes with each run
+fn is_process_running(pid: u32) -> bool {
+    let Ok(exe) = std::fs::read_link(Path::new("/proc").join(pid.to_string()).join("exe")) else {
+        return false;
+    };
+    let Ok(current_exe) = std::env::current_exe() else {
+        return true;
+    };
+
+    executable_name(&exe) == executable_name(&current_exe)
+}
+
+// the link ends with ` (deleted)` when the launcher was updated while running
+fn executable_name(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    Some(name.trim_end_matches(" (deleted)").to_string())
+}
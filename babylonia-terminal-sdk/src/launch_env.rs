@@ -0,0 +1,166 @@
+use std::{
+    collections::BTreeMap,
+    ffi::{OsStr, OsString},
+    fmt::Display,
+    path::Path,
+};
+
+use wincompatlib::wine::Wine;
+
+use crate::{
+    dxvk_config, game_config::GameConfig, game_manager::EnvironmentVariable, runner::Runner,
+};
+
+/// Where a variable of the launch environment comes from, in order of precedence,
+/// a variable set by a source override the ones set by the sources before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EnvSource {
+    Wine,
+    Runner,
+    Components,
+    LaunchOptions,
+    CommandLine,
+}
+
+impl Display for EnvSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnvSource::Wine => write!(f, "wine"),
+            EnvSource::Runner => write!(f, "runner"),
+            EnvSource::Components => write!(f, "components"),
+            EnvSource::LaunchOptions => write!(f, "launch options"),
+            EnvSource::CommandLine => write!(f, "command line"),
+        }
+    }
+}
+
+/// The variables added to the environment of the game process, and the ones removed from it
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LaunchEnv {
+    vars: BTreeMap<String, (OsString, EnvSource)>,
+    removed: BTreeMap<String, EnvSource>,
+}
+
+impl LaunchEnv {
+    /// Merge every source of variables, see [`EnvSource`] for the precedence
+    pub fn build(
+        wine: &Wine,
+        runner: &Runner,
+        config_dir: &Path,
+        config: &GameConfig,
+        launch_options_vars: Vec<(String, String)>,
+        command_line_vars: Vec<EnvironmentVariable>,
+    ) -> Self {
+        let mut env = LaunchEnv::default();
+
+        if runner.uses_wine_env() {
+            env.extend(EnvSource::Wine, wine.get_envs());
+        }
+
+        env.extend(EnvSource::Runner, runner.get_envs(config_dir));
+        for (name, value) in config.runner_settings.get_envs(runner) {
+            match value {
+                Some(value) => env.set(EnvSource::Runner, &name, value),
+                None => env.unset(EnvSource::Runner, &name),
+            }
+        }
+        env.extend(
+            EnvSource::Components,
+            get_components_envs(config_dir, config),
+        );
+        env.extend(EnvSource::LaunchOptions, launch_options_vars);
+        env.extend(
+            EnvSource::CommandLine,
+            command_line_vars.into_iter().map(|v| (v.name, v.value)),
+        );
+
+        env
+    }
+
+    pub fn set(&mut self, source: EnvSource, name: &str, value: impl AsRef<OsStr>) {
+        self.removed.remove(name);
+        self.vars
+            .insert(name.to_string(), (value.as_ref().to_os_string(), source));
+    }
+
+    /// Remove the variable inherited from the launcher, a source after it can set it again
+    pub fn unset(&mut self, source: EnvSource, name: &str) {
+        self.vars.remove(name);
+        self.removed.insert(name.to_string(), source);
+    }
+
+    pub fn extend<K, V>(&mut self, source: EnvSource, vars: impl IntoIterator<Item = (K, V)>)
+    where
+        K: AsRef<str>,
+        V: AsRef<OsStr>,
+    {
+        for (name, value) in vars {
+            self.set(source, name.as_ref(), value);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&OsStr> {
+        self.vars.get(name).map(|(value, _)| value.as_os_str())
+    }
+
+    pub fn get_source(&self, name: &str) -> Option<EnvSource> {
+        self.vars.get(name).map(|(_, source)| *source)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &OsStr)> {
+        self.vars
+            .iter()
+            .map(|(name, (value, _))| (name.as_str(), value.as_os_str()))
+    }
+
+    pub fn get_removed(&self) -> impl Iterator<Item = &str> {
+        self.removed.keys().map(String::as_str)
+    }
+
+    /// One `NAME=value (source)` or `NAME unset (source)` per line
+    pub fn dump(&self) -> String {
+        self.vars
+            .iter()
+            .map(|(name, (value, source))| {
+                format!("{}={} ({})", name, value.to_string_lossy(), source)
+            })
+            .chain(
+                self.removed
+                    .iter()
+                    .map(|(name, source)| format!("{} unset ({})", name, source)),
+            )
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+fn get_components_envs(config_dir: &Path, config: &GameConfig) -> Vec<(String, OsString)> {
+    let mut envs = vec![
+        (
+            "DXVK_STATE_CACHE_PATH".to_string(),
+            dxvk_config::get_dxvk_cache_dir(config_dir).into_os_string(),
+        ),
+        (
+            "VKD3D_SHADER_CACHE_PATH".to_string(),
+            dxvk_config::get_vkd3d_cache_dir(config_dir).into_os_string(),
+        ),
+    ];
+
+    if let Some(hud) = &config.dxvk.hud {
+        envs.push(("DXVK_HUD".to_string(), hud.into()));
+    }
+
+    if config.dxvk.to_config().is_some() {
+        envs.push((
+            "DXVK_CONFIG_FILE".to_string(),
+            dxvk_config::get_config_path(config_dir).into_os_string(),
+        ));
+    }
+
+    // VKD3D-Proton doesn't read dxvk.conf for it
+    if let Some(limit) = config.dxvk.frame_limit {
+        envs.push(("VKD3D_FRAME_RATE".to_string(), limit.to_string().into()));
+    }
+
+    envs
+}
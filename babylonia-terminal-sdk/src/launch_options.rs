@@ -0,0 +1,292 @@
+use std::ffi::OsString;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::game_manager::EnvironmentVariable;
+
+pub static COMMAND_PLACEHOLDER: &str = "%command%";
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LaunchOptions {
+    /// Start the game with `gamemoderun`
+    pub gamemode: bool,
+    /// Start the game with `mangohud`
+    pub mangohud: bool,
+    /// Start the game inside gamescope, with these arguments
+    pub gamescope: Option<Vec<String>>,
+    /// Passed to the game after its own arguments
+    pub game_args: Vec<String>,
+    pub env_vars: Vec<(String, String)>,
+    /// Options like on Steam, `FOO=bar mangohud %command% -arg`, parsed with the shell rules
+    pub raw: Option<String>,
+}
+
+/// What [`LaunchOptions::build`] produced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchCommand {
+    pub command: Vec<OsString>,
+    pub env_vars: Vec<(String, String)>,
+}
+
+impl LaunchOptions {
+    /// Wrap `game_command` with the wrappers and add the arguments of the game
+    pub fn build(&self, game_command: Vec<OsString>) -> anyhow::Result<LaunchCommand> {
+        let raw = match &self.raw {
+            Some(raw) => RawOptions::parse(raw)?,
+            None => RawOptions::default(),
+        };
+
+        let mut command: Vec<OsString> = raw.wrappers.iter().map(OsString::from).collect();
+
+        if let Some(args) = &self.gamescope {
+            command.push("gamescope".into());
+            command.extend(args.iter().map(OsString::from));
+            command.push("--".into());
+        }
+
+        if self.gamemode {
+            command.push("gamemoderun".into());
+        }
+
+        if self.mangohud {
+            command.push("mangohud".into());
+        }
+
+        command.extend(game_command);
+        command.extend(self.game_args.iter().map(OsString::from));
+        command.extend(raw.args.iter().map(OsString::from));
+
+        // the variables of the raw options are more specific, so they come last
+        let mut env_vars = self.env_vars.clone();
+        env_vars.extend(raw.env_vars);
+
+        Ok(LaunchCommand { command, env_vars })
+    }
+
+    /// Parse arguments written like in a shell, `-a "b c"` give `["-a", "b c"]`
+    pub fn parse_args(args: &str) -> anyhow::Result<Vec<String>> {
+        shell_words::split(args)
+            .map_err(|e| anyhow::anyhow!("Invalid arguments '{}' : {}", args, e))
+    }
+
+    pub fn format_args(args: &[String]) -> String {
+        shell_words::join(args)
+    }
+
+    /// Replace the value if the variable is already set
+    pub fn set_env_var(&mut self, variable: EnvironmentVariable) {
+        match self
+            .env_vars
+            .iter_mut()
+            .find(|(name, _)| *name == variable.name)
+        {
+            Some((_, value)) => *value = variable.value,
+            None => self.env_vars.push((variable.name, variable.value)),
+        }
+    }
+
+    /// Return false if the variable wasn't set
+    pub fn unset_env_var(&mut self, name: &str) -> bool {
+        let len = self.env_vars.len();
+        self.env_vars.retain(|(n, _)| n != name);
+        len != self.env_vars.len()
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct RawOptions {
+    env_vars: Vec<(String, String)>,
+    wrappers: Vec<String>,
+    args: Vec<String>,
+}
+
+impl RawOptions {
+    fn parse(raw: &str) -> anyhow::Result<Self> {
+        let mut tokens = LaunchOptions::parse_args(raw)?.into_iter().peekable();
+        let mut options = RawOptions::default();
+
+        while let Some(variable) = tokens.peek().and_then(|t| parse_env_assignment(t)) {
+            options.env_vars.push(variable);
+            tokens.next();
+        }
+
+        let tokens: Vec<String> = tokens.collect();
+
+        // without %command%, everything is passed to the game
+        match tokens.iter().position(|t| t == COMMAND_PLACEHOLDER) {
+            Some(index) => {
+                options.wrappers = tokens[..index].to_vec();
+                options.args = tokens[(index + 1)..].to_vec();
+            }
+            None => options.args = tokens,
+        }
+
+        Ok(options)
+    }
+}
+
+fn parse_env_assignment(token: &str) -> Option<(String, String)> {
+    let (name, value) = token.split_once('=')?;
+
+    if EnvironmentVariable::is_valid_name(name) {
+        Some((name.to_string(), value.to_string()))
+    } else {
+        None
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredLaunchOptions {
+    Legacy(Option<String>),
+    Options(LaunchOptions),
+}
+
+/// The launch options were a single string before, it's kept as the raw options
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<LaunchOptions, D::Error> {
+    Ok(match StoredLaunchOptions::deserialize(deserializer)? {
+        StoredLaunchOptions::Legacy(raw) => LaunchOptions {
+            raw,
+            ..Default::default()
+        },
+        StoredLaunchOptions::Options(options) => options,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw(raw: &str) -> LaunchOptions {
+        LaunchOptions {
+            raw: Some(raw.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn build(options: &LaunchOptions) -> (Vec<String>, Vec<(String, String)>) {
+        let LaunchCommand { command, env_vars } = options
+            .build(vec!["wine".into(), "game.exe".into()])
+            .unwrap();
+
+        let command = command
+            .into_iter()
+            .map(|arg| arg.into_string().unwrap())
+            .collect();
+        (command, env_vars)
+    }
+
+    fn env(name: &str, value: &str) -> (String, String) {
+        (name.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn split_around_command() {
+        let (command, env_vars) = build(&raw(
+            r#"FOO=bar DXVK_HUD="fps,gpuload" mangohud %command% -arg "a b""#,
+        ));
+
+        assert_eq!(command, ["mangohud", "wine", "game.exe", "-arg", "a b"]);
+        assert_eq!(
+            env_vars,
+            [env("FOO", "bar"), env("DXVK_HUD", "fps,gpuload")]
+        );
+    }
+
+    #[test]
+    fn without_command() {
+        // everything is passed to the game, except the variables at the start
+        let (command, env_vars) = build(&raw("FOO=bar -popupwindow -screen-fullscreen 0"));
+
+        assert_eq!(
+            command,
+            [
+                "wine",
+                "game.exe",
+                "-popupwindow",
+                "-screen-fullscreen",
+                "0"
+            ]
+        );
+        assert_eq!(env_vars, [env("FOO", "bar")]);
+    }
+
+    #[test]
+    fn only_leading_assignments() {
+        let (command, env_vars) = build(&raw("1FOO=bar strace FOO=bar %command%"));
+
+        assert_eq!(
+            command,
+            ["1FOO=bar", "strace", "FOO=bar", "wine", "game.exe"]
+        );
+        assert!(env_vars.is_empty());
+    }
+
+    #[test]
+    fn wrappers_order() {
+        let options = LaunchOptions {
+            gamemode: true,
+            mangohud: true,
+            gamescope: Some(vec!["-W".to_string(), "1920".to_string(), "-f".to_string()]),
+            game_args: vec!["-a".to_string()],
+            env_vars: vec![env("FOO", "config")],
+            raw: Some("FOO=raw strace %command% -b".to_string()),
+        };
+        let (command, env_vars) = build(&options);
+
+        assert_eq!(
+            command,
+            [
+                "strace",
+                "gamescope",
+                "-W",
+                "1920",
+                "-f",
+                "--",
+                "gamemoderun",
+                "mangohud",
+                "wine",
+                "game.exe",
+                "-a",
+                "-b"
+            ]
+        );
+        // the raw variables come last so they win
+        assert_eq!(env_vars, [env("FOO", "config"), env("FOO", "raw")]);
+    }
+
+    #[test]
+    fn invalid_raw_options() {
+        assert!(raw(r#"mangohud %command% "-arg"#)
+            .build(vec!["wine".into()])
+            .is_err());
+    }
+
+    #[test]
+    fn deserialize_legacy_string() {
+        let options = deserialize(serde_json::json!("mangohud %command% -arg")).unwrap();
+        assert_eq!(options, raw("mangohud %command% -arg"));
+
+        let options = deserialize(serde_json::Value::Null).unwrap();
+        assert_eq!(options, LaunchOptions::default());
+    }
+
+    #[test]
+    fn deserialize_options() {
+        let options = deserialize(serde_json::json!({
+            "gamemode": true,
+            "game_args": ["-a"],
+        }))
+        .unwrap();
+
+        assert_eq!(
+            options,
+            LaunchOptions {
+                gamemode: true,
+                game_args: vec!["-a".to_string()],
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -1,6 +1,24 @@
 pub mod components;
+pub mod desktop_entry;
+pub mod diagnostics;
+pub mod dxvk_config;
 pub mod game_config;
+pub mod game_logs;
 pub mod game_manager;
 pub mod game_patcher;
+pub mod game_process;
 pub mod game_state;
+pub mod hooks;
+pub mod instance_lock;
+pub mod launch_env;
+pub mod launch_options;
+pub mod player_prefs;
+pub mod playtime;
+pub mod prefix_manifest;
+pub mod prefix_settings;
+pub mod prepared_launch;
+pub mod runner;
+pub mod runner_settings;
+pub mod steam_shortcut;
+pub mod unity_player;
 pub mod utils;
@@ -0,0 +1,303 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use log::debug;
+use tokio::fs::read_to_string;
+
+use crate::utils::{
+    get_game_name,
+    registry_file::{RegistryData, RegistryFile, REG_BINARY},
+};
+
+pub static REGISTRY_FILE: &str = "user.reg";
+
+/// The settings saved by the Unity player, the other values are the settings of the game
+static GRAPHICS_PREFS: [&str; 2] = ["Screenmanager ", "UnityGraphicsQuality"];
+
+/// The PlayerPrefs of Unity, saved in `HKCU\Software\<company>\<product>` of the prefix. The
+/// registry is edited without wine, so the game and wine need to be closed
+#[derive(Debug, Clone)]
+pub struct PlayerPrefs {
+    path: PathBuf,
+    key: String,
+    registry: RegistryFile,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerPref {
+    /// Without the hash added by Unity
+    pub name: String,
+    pub registry_name: String,
+    pub value: PrefValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrefValue {
+    Int(i32),
+    Float(f64),
+    String(String),
+    /// A value not written by Unity
+    Other(String),
+}
+
+impl PlayerPrefs {
+    pub async fn load(game_dir: &Path, prefix: &Path) -> anyhow::Result<Self> {
+        let (company, product) = get_app_info(game_dir).await?;
+        let key = format!(r"Software\{}\{}", company, product);
+        let path = prefix.join(REGISTRY_FILE);
+
+        debug!("Reading the PlayerPrefs of {} in {:?}", key, path);
+        let registry = RegistryFile::load(&path).await?;
+
+        Ok(PlayerPrefs {
+            path,
+            key,
+            registry,
+        })
+    }
+
+    /// Use [`crate::game_manager::GameManager::edit_player_prefs`], wine needs to be closed
+    pub(crate) async fn save(&self) -> anyhow::Result<()> {
+        self.registry.save(&self.path).await
+    }
+
+    pub fn list(&self) -> Vec<PlayerPref> {
+        self.registry
+            .get_values(&self.key)
+            .into_iter()
+            .map(|v| PlayerPref {
+                name: get_pref_name(&v.name).to_string(),
+                value: PrefValue::from_registry(&v.data),
+                registry_name: v.name,
+            })
+            .collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<PlayerPref> {
+        self.list().into_iter().find(|p| p.name == name)
+    }
+
+    /// Write `value` like Unity would, the game needs to have been started once
+    pub fn set(&mut self, name: &str, value: &PrefValue) -> anyhow::Result<()> {
+        let Some(data) = value.to_registry() else {
+            anyhow::bail!("{} can't be written to the PlayerPrefs", value);
+        };
+
+        if !self.registry.has_key(&self.key) {
+            anyhow::bail!("The game needs to be started once before changing its settings");
+        }
+
+        self.registry
+            .set_value(&self.key, &get_registry_name(name), &data)
+    }
+
+    /// Return false if the value wasn't set
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.registry
+            .remove_values(&self.key, |n| get_pref_name(n) == name)
+            > 0
+    }
+
+    /// Remove the resolution, the window mode and the quality, the game starts with its default
+    /// ones next time, return how many values were removed
+    pub fn reset_graphics(&mut self) -> usize {
+        self.registry.remove_values(&self.key, |n| {
+            GRAPHICS_PREFS
+                .iter()
+                .any(|p| get_pref_name(n).starts_with(p))
+        })
+    }
+}
+
+impl PrefValue {
+    fn from_registry(data: &RegistryData) -> Self {
+        match data {
+            RegistryData::Dword(value) => PrefValue::Int(*value as i32),
+            // the strings are null-terminated UTF-8 and the floats are doubles, a double like 0.0
+            // also ends with a null byte but it's not a valid string
+            RegistryData::Hex { bytes, .. } => match get_string(bytes) {
+                Some(value) => PrefValue::String(value.to_string()),
+                None if bytes.len() == 8 => {
+                    PrefValue::Float(f64::from_le_bytes(bytes[..].try_into().unwrap()))
+                }
+                None => PrefValue::Other(data.to_string()),
+            },
+            data => PrefValue::Other(data.to_string()),
+        }
+    }
+
+    fn to_registry(&self) -> Option<RegistryData> {
+        match self {
+            PrefValue::Int(value) => Some(RegistryData::Dword(*value as u32)),
+            PrefValue::Float(value) => Some(RegistryData::Hex {
+                kind: REG_BINARY,
+                bytes: value.to_le_bytes().to_vec(),
+            }),
+            PrefValue::String(value) => {
+                let mut bytes = value.as_bytes().to_vec();
+                bytes.push(0);
+                Some(RegistryData::Hex {
+                    kind: REG_BINARY,
+                    bytes,
+                })
+            }
+            PrefValue::Other(_) => None,
+        }
+    }
+
+    /// Parse `value` with the type of `previous`, or as an integer, a float or a string if
+    /// there's no previous value
+    pub fn parse(value: &str, previous: Option<&PrefValue>) -> anyhow::Result<Self> {
+        match previous {
+            Some(PrefValue::Int(_)) => {
+                Ok(PrefValue::Int(value.parse().map_err(|_| {
+                    anyhow::anyhow!("'{}' is not an integer", value)
+                })?))
+            }
+            Some(PrefValue::Float(_)) => {
+                Ok(PrefValue::Float(value.parse().map_err(|_| {
+                    anyhow::anyhow!("'{}' is not a number", value)
+                })?))
+            }
+            Some(PrefValue::Other(_)) => anyhow::bail!("This value was not written by Unity"),
+            Some(PrefValue::String(_)) => Ok(PrefValue::String(value.to_string())),
+            None => Ok(value
+                .parse()
+                .map(PrefValue::Int)
+                .or_else(|_| value.parse().map(PrefValue::Float))
+                .unwrap_or_else(|_| PrefValue::String(value.to_string()))),
+        }
+    }
+}
+
+impl Display for PrefValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrefValue::Int(value) => write!(f, "{}", value),
+            PrefValue::Float(value) => write!(f, "{}", value),
+            PrefValue::String(value) => write!(f, "\"{}\"", value),
+            PrefValue::Other(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// `<game>_Data/app.info` contains the company on the first line and the product on the second
+async fn get_app_info(game_dir: &Path) -> anyhow::Result<(String, String)> {
+    let path = game_dir
+        .join(get_game_name())
+        .join(format!("{}_Data", get_game_name()))
+        .join("app.info");
+
+    let content = read_to_string(&path)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to read {:?} : {}", path, e))?;
+
+    let mut lines = content.lines().map(str::trim);
+    match (lines.next(), lines.next()) {
+        (Some(company), Some(product)) if !company.is_empty() && !product.is_empty() => {
+            Ok((company.to_string(), product.to_string()))
+        }
+        _ => anyhow::bail!("Invalid app.info, expected the company and the product"),
+    }
+}
+
+// only one null byte, at the end
+fn get_string(bytes: &[u8]) -> Option<&str> {
+    let value = bytes.strip_suffix(&[0])?;
+    if value.contains(&0) {
+        return None;
+    }
+
+    std::str::from_utf8(value).ok()
+}
+
+// Unity adds `_h` and a hash of the name to the name of the registry value
+fn get_hash(name: &str) -> u32 {
+    name.bytes()
+        .fold(5381u32, |hash, b| hash.wrapping_mul(33) ^ b as u32)
+}
+
+fn get_registry_name(name: &str) -> String {
+    format!("{}_h{}", name, get_hash(name))
+}
+
+fn get_pref_name(registry_name: &str) -> &str {
+    match registry_name.rsplit_once("_h") {
+        Some((name, hash)) if hash.parse::<u32>().is_ok_and(|h| h == get_hash(name)) => name,
+        _ => registry_name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_like_unity() {
+        assert_eq!(
+            get_registry_name("Screenmanager Resolution Width"),
+            "Screenmanager Resolution Width_h182942802"
+        );
+        assert_eq!(
+            get_registry_name("UnityGraphicsQuality"),
+            "UnityGraphicsQuality_h1669003810"
+        );
+        assert_eq!(
+            get_pref_name("Screenmanager Resolution Height_h2627697771"),
+            "Screenmanager Resolution Height"
+        );
+        // a wrong hash is part of the name
+        assert_eq!(get_pref_name("Volume_h1"), "Volume_h1");
+    }
+
+    fn hex(bytes: &[u8]) -> RegistryData {
+        RegistryData::Hex {
+            kind: REG_BINARY,
+            bytes: bytes.to_vec(),
+        }
+    }
+
+    #[test]
+    fn read_values() {
+        assert_eq!(
+            PrefValue::from_registry(&RegistryData::Dword(0xffffffff)),
+            PrefValue::Int(-1)
+        );
+        assert_eq!(
+            PrefValue::from_registry(&hex(b"English\0")),
+            PrefValue::String("English".to_string())
+        );
+        assert_eq!(
+            PrefValue::from_registry(&hex(b"\0")),
+            PrefValue::String(String::new())
+        );
+        assert_eq!(
+            PrefValue::from_registry(&hex(&0.0f64.to_le_bytes())),
+            PrefValue::Float(0.0)
+        );
+        assert_eq!(
+            PrefValue::from_registry(&hex(&0.75f64.to_le_bytes())),
+            PrefValue::Float(0.75)
+        );
+        assert_eq!(
+            PrefValue::from_registry(&hex(&[0xff, 0xfe, 0])),
+            PrefValue::Other("hex:ff,fe,00".to_string())
+        );
+    }
+
+    #[test]
+    fn write_values_back() {
+        for value in [
+            PrefValue::Int(-1),
+            PrefValue::Float(0.0),
+            PrefValue::Float(1.5),
+            PrefValue::String("English".to_string()),
+            PrefValue::String(String::new()),
+        ] {
+            let data = value.to_registry().unwrap();
+            assert_eq!(PrefValue::from_registry(&data), value);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::{io::ErrorKind, path::PathBuf};
+
+use chrono::TimeDelta;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use tokio::fs::{read_to_string, rename};
+
+use crate::{game_config::GameConfig, game_logs::SessionInfo, utils::write_atomic};
+
+static HISTORY_FILE: &str = "playtime-history.json";
+
+/// Every launch of the game, unlike the logs they are never removed
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaytimeHistory {
+    pub sessions: Vec<SessionInfo>,
+}
+
+impl PlaytimeHistory {
+    async fn get_history_file_path() -> PathBuf {
+        GameConfig::get_config_directory().await.join(HISTORY_FILE)
+    }
+
+    /// Empty if the game was never started, fails if the history can't be read
+    pub async fn load() -> anyhow::Result<Self> {
+        let path = Self::get_history_file_path().await;
+
+        match read_to_string(&path).await {
+            Ok(content) => match serde_json::from_str(&content) {
+                Ok(history) => Ok(history),
+                Err(e) => anyhow::bail!("The playtime history {:?} is not valid : {}", path, e),
+            },
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn add_session(session: SessionInfo) -> anyhow::Result<()> {
+        let path = Self::get_history_file_path().await;
+
+        let mut history = match Self::load().await {
+            Ok(history) => history,
+            // the history is kept aside instead of being replaced
+            Err(e) if path.exists() => {
+                let backup = path.with_extension("json.bak");
+                warn!("{}, it's moved to {:?}", e, backup);
+                rename(&path, &backup).await?;
+                Self::default()
+            }
+            Err(e) => return Err(e),
+        };
+        history.sessions.push(session);
+
+        write_atomic(&path, serde_json::to_string(&history)?).await?;
+
+        Ok(())
+    }
+
+    pub fn get_total_playtime(&self) -> TimeDelta {
+        self.sessions
+            .iter()
+            .filter_map(SessionInfo::get_duration)
+            .sum()
+    }
+
+    pub fn get_last_session(&self) -> Option<&SessionInfo> {
+        self.sessions.iter().max_by_key(|s| s.started_at)
+    }
+}
+
+/// Like `12h 05m`
+pub fn format_playtime(playtime: TimeDelta) -> String {
+    format!(
+        "{}h {:02}m",
+        playtime.num_hours(),
+        playtime.num_minutes() % 60
+    )
+}
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use wincompatlib::prelude::Font;
+
+/// Winetricks verbs installing CJK fonts, for the Asian languages of the game
+pub static CJK_FONTS: [&str; 4] = ["cjkfonts", "fakechinese", "fakejapanese", "fakekorean"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub name: String,
+    pub is_installed: bool,
+}
+
+impl ManifestEntry {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            is_installed: false,
+        }
+    }
+}
+
+/// What need to be installed in the prefix, the entries not installed yet
+/// are installed on the next run of the launcher.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrefixManifest {
+    pub verbs: Vec<ManifestEntry>,
+    pub fonts: Vec<ManifestEntry>,
+}
+
+impl Default for PrefixManifest {
+    fn default() -> Self {
+        Self {
+            verbs: vec![ManifestEntry::new("vcrun2022")],
+            fonts: vec![ManifestEntry::new("arial")],
+        }
+    }
+}
+
+impl PrefixManifest {
+    /// The manifest of a config written before it existed, the default fonts and verbs were
+    /// installed if the fonts and the dependencies were
+    pub fn from_legacy(is_font_installed: bool, is_dependecies_installed: bool) -> Self {
+        let mut manifest = Self::default();
+        manifest
+            .fonts
+            .iter_mut()
+            .for_each(|e| e.is_installed = is_font_installed);
+        manifest
+            .verbs
+            .iter_mut()
+            .for_each(|e| e.is_installed = is_dependecies_installed);
+
+        manifest
+    }
+
+    pub fn get_pending_verbs(&self) -> Vec<String> {
+        get_pending(&self.verbs)
+    }
+
+    pub fn get_pending_fonts(&self) -> Vec<String> {
+        get_pending(&self.fonts)
+    }
+
+    /// Return false if the verb is already in the manifest
+    pub fn add_verb(&mut self, verb: &str) -> bool {
+        add(&mut self.verbs, verb)
+    }
+
+    /// Return false if the font is already in the manifest
+    pub fn add_font(&mut self, font: &str) -> bool {
+        add(&mut self.fonts, font)
+    }
+
+    pub fn remove_verb(&mut self, verb: &str) {
+        self.verbs.retain(|e| e.name != verb);
+    }
+
+    pub fn remove_font(&mut self, font: &str) {
+        self.fonts.retain(|e| e.name != font);
+    }
+
+    pub fn set_verb_installed(&mut self, verb: &str, is_installed: bool) {
+        set_installed(&mut self.verbs, verb, is_installed);
+    }
+
+    pub fn set_font_installed(&mut self, font: &str, is_installed: bool) {
+        set_installed(&mut self.fonts, font, is_installed);
+    }
+
+    /// Mark everything as not installed, used when the prefix is recreated
+    pub fn reset(&mut self) {
+        self.verbs.iter_mut().for_each(|e| e.is_installed = false);
+        self.fonts.iter_mut().for_each(|e| e.is_installed = false);
+    }
+}
+
+/// The fonts bundled with wincompatlib, the other fonts are installed with winetricks
+pub fn get_wincompatlib_font(name: &str) -> Option<Font> {
+    match name.to_lowercase().as_str() {
+        "andale" => Some(Font::Andale),
+        "arial" => Some(Font::Arial),
+        "courier" => Some(Font::Courier),
+        "georgia" => Some(Font::Georgia),
+        "impact" => Some(Font::Impact),
+        "times" => Some(Font::Times),
+        "trebuchet" => Some(Font::Trebuchet),
+        "verdana" => Some(Font::Verdana),
+        "webdings" => Some(Font::Webdings),
+        _ => None,
+    }
+}
+
+fn get_pending(entries: &[ManifestEntry]) -> Vec<String> {
+    entries
+        .iter()
+        .filter(|e| !e.is_installed)
+        .map(|e| e.name.clone())
+        .collect()
+}
+
+fn add(entries: &mut Vec<ManifestEntry>, name: &str) -> bool {
+    let name = name.trim();
+    if name.is_empty() || entries.iter().any(|e| e.name == name) {
+        return false;
+    }
+
+    entries.push(ManifestEntry::new(name));
+    true
+}
+
+fn set_installed(entries: &mut [ManifestEntry], name: &str, is_installed: bool) {
+    if let Some(entry) = entries.iter_mut().find(|e| e.name == name) {
+        entry.is_installed = is_installed;
+    }
+}
@@ -0,0 +1,238 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use wincompatlib::wine::Wine;
+
+use crate::{
+    components::{dxvk_component::DXVK_DLLS, vkd3d_component::VKD3D_DLLS},
+    utils::prefix_registry,
+};
+
+/// The settings of the wine prefix usually changed with winecfg
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrefixSettings {
+    /// Keep the version set by wine when it's not defined
+    pub windows_version: Option<WindowsVersion>,
+    pub dpi: Option<u32>,
+    pub virtual_desktop: Option<VirtualDesktop>,
+    /// dll name -> mode, the mode is the value used in the registry, like `native,builtin`
+    pub dll_overrides: BTreeMap<String, String>,
+}
+
+impl PrefixSettings {
+    /// Write the settings to the registry of the prefix, `previous` is used to revert what's not
+    /// set anymore.
+    ///
+    /// `replaced_overrides` keeps the mode each dll had before the user overrode it, so removing
+    /// an override gives back the one set by DXVK or VKD3D-Proton instead of deleting it
+    pub fn apply(
+        &self,
+        wine: &Wine,
+        previous: Option<&PrefixSettings>,
+        replaced_overrides: &mut BTreeMap<String, Option<String>>,
+    ) -> anyhow::Result<()> {
+        match self.windows_version {
+            Some(version) => prefix_registry::set_windows_version(wine, &version.to_string())?,
+            None if previous.is_some_and(|p| p.windows_version.is_some()) => {
+                prefix_registry::set_windows_version(wine, &DEFAULT_WINDOWS_VERSION.to_string())?
+            }
+            None => {}
+        }
+
+        match self.dpi {
+            Some(dpi) => prefix_registry::set_dpi(wine, dpi)?,
+            None if previous.is_some_and(|p| p.dpi.is_some()) => {
+                prefix_registry::set_dpi(wine, DEFAULT_DPI)?
+            }
+            None => {}
+        }
+
+        match self.virtual_desktop {
+            Some(desktop) => {
+                prefix_registry::set_virtual_desktop(wine, desktop.width, desktop.height)?
+            }
+            None => prefix_registry::remove_virtual_desktop(wine)?,
+        }
+
+        if let Some(previous) = previous {
+            for dll in previous.dll_overrides.keys() {
+                if !self.dll_overrides.contains_key(dll) {
+                    Self::restore_dll_override(wine, dll, replaced_overrides.remove(dll))?;
+                }
+            }
+        }
+
+        for (dll, mode) in &self.dll_overrides {
+            if !replaced_overrides.contains_key(dll) {
+                let replaced = prefix_registry::get_dll_override(wine, dll)?;
+                replaced_overrides.insert(dll.clone(), replaced);
+            }
+
+            prefix_registry::set_dll_override(wine, dll, mode)?;
+        }
+
+        Ok(())
+    }
+
+    fn restore_dll_override(
+        wine: &Wine,
+        dll: &str,
+        replaced: Option<Option<String>>,
+    ) -> anyhow::Result<()> {
+        match replaced {
+            Some(Some(mode)) => prefix_registry::set_dll_override(wine, dll, &mode),
+            Some(None) => prefix_registry::remove_dll_override(wine, dll),
+            // overridden before the replaced modes were kept, at least don't lose the components
+            None if DXVK_DLLS.contains(&dll) || VKD3D_DLLS.contains(&dll) => {
+                prefix_registry::set_dll_override(wine, dll, "native")
+            }
+            None => prefix_registry::remove_dll_override(wine, dll),
+        }
+    }
+
+    /// Parse the overrides with the format of `WINEDLLOVERRIDES`, like `d3d11,dxgi=n,b;mscoree=`
+    pub fn parse_dll_overrides(overrides: &str) -> anyhow::Result<BTreeMap<String, String>> {
+        let mut result = BTreeMap::new();
+
+        for group in overrides
+            .split(';')
+            .map(str::trim)
+            .filter(|g| !g.is_empty())
+        {
+            let (dlls, mode) = match group.split_once('=') {
+                Some((dlls, mode)) => (dlls, mode.trim()),
+                None => anyhow::bail!("The mode of '{}' is missing, like {}=n,b", group, group),
+            };
+
+            let mode = mode
+                .split(',')
+                .map(str::trim)
+                .filter(|m| !m.is_empty())
+                .map(|m| match m {
+                    "n" | "native" => Ok("native"),
+                    "b" | "builtin" => Ok("builtin"),
+                    _ => anyhow::bail!("Unknown dll mode '{}', expected n or b", m),
+                })
+                .collect::<anyhow::Result<Vec<&str>>>()?
+                .join(",");
+
+            for dll in dlls.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+                result.insert(dll.to_lowercase(), mode.clone());
+            }
+        }
+
+        Ok(result)
+    }
+
+    pub fn format_dll_overrides(&self) -> String {
+        self.dll_overrides
+            .iter()
+            .map(|(dll, mode)| {
+                let mode = mode
+                    .split(',')
+                    .filter(|m| !m.is_empty())
+                    .map(|m| if m == "native" { "n" } else { "b" })
+                    .collect::<Vec<&str>>()
+                    .join(",");
+                format!("{}={}", dll, mode)
+            })
+            .collect::<Vec<String>>()
+            .join(";")
+    }
+}
+
+pub static DEFAULT_DPI: u32 = 96;
+pub static DEFAULT_WINDOWS_VERSION: WindowsVersion = WindowsVersion::Win10;
+
+pub fn check_dpi(dpi: u32) -> anyhow::Result<()> {
+    if !(72..=480).contains(&dpi) {
+        anyhow::bail!("The DPI must be between 72 and 480, got {}", dpi);
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindowsVersion {
+    Win7,
+    Win8,
+    Win81,
+    Win10,
+    Win11,
+}
+
+impl WindowsVersion {
+    pub fn all() -> [WindowsVersion; 5] {
+        [
+            WindowsVersion::Win7,
+            WindowsVersion::Win8,
+            WindowsVersion::Win81,
+            WindowsVersion::Win10,
+            WindowsVersion::Win11,
+        ]
+    }
+}
+
+// the names used by winecfg
+impl Display for WindowsVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WindowsVersion::Win7 => write!(f, "win7"),
+            WindowsVersion::Win8 => write!(f, "win8"),
+            WindowsVersion::Win81 => write!(f, "win81"),
+            WindowsVersion::Win10 => write!(f, "win10"),
+            WindowsVersion::Win11 => write!(f, "win11"),
+        }
+    }
+}
+
+impl FromStr for WindowsVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "win7" | "7" => Ok(WindowsVersion::Win7),
+            "win8" | "8" => Ok(WindowsVersion::Win8),
+            "win81" | "8.1" => Ok(WindowsVersion::Win81),
+            "win10" | "10" => Ok(WindowsVersion::Win10),
+            "win11" | "11" => Ok(WindowsVersion::Win11),
+            _ => anyhow::bail!(
+                "Unknown Windows version '{}', expected win7, win8, win81, win10 or win11",
+                s
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VirtualDesktop {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Display for VirtualDesktop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+impl FromStr for VirtualDesktop {
+    type Err = anyhow::Error;
+
+    /// Parse a size like `1920x1080`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let size = s.trim().split_once('x').and_then(|(w, h)| {
+            Some((w.trim().parse::<u32>().ok()?, h.trim().parse::<u32>().ok()?))
+        });
+
+        match size {
+            Some((width, height)) if width > 0 && height > 0 => {
+                Ok(VirtualDesktop { width, height })
+            }
+            _ => anyhow::bail!(
+                "Invalid virtual desktop size '{}', expected WIDTHxHEIGHT",
+                s
+            ),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::{ffi::OsString, path::PathBuf, process::Stdio};
+
+use tokio::process::{Child, Command};
+
+use crate::launch_env::LaunchEnv;
+
+/// Everything needed to start the game, resolved without starting it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparedLaunch {
+    pub command: Vec<OsString>,
+    pub working_dir: PathBuf,
+    pub env: LaunchEnv,
+}
+
+impl PreparedLaunch {
+    pub fn spawn(&self) -> std::io::Result<Child> {
+        let mut command = Command::new(&self.command[0]);
+        for name in self.env.get_removed() {
+            command.env_remove(name);
+        }
+
+        command
+            .args(&self.command[1..])
+            .current_dir(&self.working_dir)
+            // to stop the wrappers and wine with the game
+            .process_group(0)
+            .envs(self.env.iter())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+    }
+
+    /// The arguments quoted like in a shell
+    pub fn get_command_line(&self) -> String {
+        self.command
+            .iter()
+            .map(|arg| shell_words::quote(&arg.to_string_lossy()).into_owned())
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// A command to paste in a terminal to start the game like the launcher does, the variables
+    /// not set by the launcher are inherited from the terminal
+    pub fn to_shell_command(&self) -> String {
+        let env = self
+            .env
+            .get_removed()
+            .map(|name| format!("-u {}", name))
+            .chain(self.env.iter().map(|(name, value)| {
+                format!("{}={}", name, shell_words::quote(&value.to_string_lossy()))
+            }))
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        format!(
+            "cd {} && env {} {}",
+            shell_words::quote(&self.working_dir.to_string_lossy()),
+            env,
+            self.get_command_line()
+        )
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    ffi::OsString,
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The program used to start the game.
+///
+/// Proton and umu-launcher are managing their own prefix in `<config>/proton/pfx`,
+/// the components installed in the wine prefix are not used by them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Runner {
+    /// The wine build downloaded by the launcher
+    Bundled,
+    /// The `wine` binary found in the $PATH
+    System,
+    /// The `proton` script of a Proton build, like GE-Proton
+    Proton { path: PathBuf },
+    /// `umu-run`, with an optional Proton build, umu-launcher download one if it's not specified
+    Umu { proton_path: Option<PathBuf> },
+    /// A custom program, the game executable is passed as the last argument
+    Custom { program: PathBuf, args: Vec<String> },
+}
+
+impl Default for Runner {
+    fn default() -> Self {
+        if let Some(distro) = whatadistro::identify() {
+            if distro.is_similar("nixos") {
+                return Runner::System;
+            }
+        }
+
+        Runner::Bundled
+    }
+}
+
+impl Runner {
+    pub fn get_wine_binary(&self, config_dir: &Path) -> PathBuf {
+        match self {
+            Runner::System => PathBuf::from("wine"),
+            _ => config_dir.join("wine").join("bin").join("wine"),
+        }
+    }
+
+    pub fn get_proton_data_path(config_dir: &Path) -> PathBuf {
+        config_dir.join("proton")
+    }
+
+    /// The wineserver of the wine used by the runner, umu-launcher chooses its own Proton build so
+    /// it's unknown
+    pub fn get_wineserver(&self, config_dir: &Path) -> Option<PathBuf> {
+        match self {
+            // the older builds have it in dist
+            Runner::Proton { path } => ["files", "dist"]
+                .iter()
+                .map(|dir| path.join(dir).join("bin").join("wineserver"))
+                .find(|wineserver| wineserver.is_file())
+                .or_else(|| Some(path.join("files").join("bin").join("wineserver"))),
+            Runner::Umu { .. } => None,
+            _ => Some(
+                self.get_wine_binary(config_dir)
+                    .with_file_name("wineserver"),
+            ),
+        }
+    }
+
+    /// The wine prefix used by the game
+    pub fn get_prefix_path(&self, config_dir: &Path) -> PathBuf {
+        match self {
+            Runner::Proton { .. } | Runner::Umu { .. } => {
+                Self::get_proton_data_path(config_dir).join("pfx")
+            }
+            _ => config_dir.join("data"),
+        }
+    }
+
+    pub fn get_command(&self, config_dir: &Path, executable: &Path) -> Vec<OsString> {
+        let mut command: Vec<OsString> = match self {
+            Runner::Bundled | Runner::System => vec![self.get_wine_binary(config_dir).into()],
+            Runner::Proton { path } => vec![path.join("proton").into(), "run".into()],
+            Runner::Umu { .. } => vec!["umu-run".into()],
+            Runner::Custom { program, args } => {
+                let mut command = vec![program.clone().into_os_string()];
+                command.extend(args.iter().map(OsString::from));
+                command
+            }
+        };
+
+        command.push(executable.into());
+        command
+    }
+
+    /// Parse the command of a custom runner, the program followed by its arguments, split with the
+    /// shell rules
+    pub fn parse_custom_command(command: &str) -> anyhow::Result<Self> {
+        let mut words = shell_words::split(command)?.into_iter();
+
+        match words.next() {
+            Some(program) => Ok(Runner::Custom {
+                program: PathBuf::from(program),
+                args: words.collect(),
+            }),
+            None => anyhow::bail!("The program to use is missing, use custom:<program> <args>"),
+        }
+    }
+
+    /// The command of a custom runner, as parsed by [`Runner::parse_custom_command`]
+    pub fn format_custom_command(program: &Path, args: &[String]) -> String {
+        let mut words = vec![program.to_string_lossy().into_owned()];
+        words.extend(args.iter().cloned());
+        shell_words::join(words)
+    }
+
+    /// Proton and umu-launcher create and update their prefix themselves, the launcher can't
+    /// change its settings
+    pub fn manages_own_prefix(&self) -> bool {
+        matches!(self, Runner::Proton { .. } | Runner::Umu { .. })
+    }
+
+    /// Proton and umu-launcher are bringing their own wine, the variables of wincompatlib would
+    /// make them use the wine of the launcher
+    pub fn uses_wine_env(&self) -> bool {
+        !matches!(self, Runner::Proton { .. } | Runner::Umu { .. })
+    }
+
+    pub fn get_envs(&self, config_dir: &Path) -> Vec<(String, OsString)> {
+        let data_path = Self::get_proton_data_path(config_dir);
+
+        match self {
+            Runner::Proton { .. } => vec![
+                ("STEAM_COMPAT_DATA_PATH".to_string(), data_path.into()),
+                // the proton script only need this variable to be defined
+                (
+                    "STEAM_COMPAT_CLIENT_INSTALL_PATH".to_string(),
+                    config_dir.into(),
+                ),
+            ],
+            Runner::Umu { proton_path } => {
+                let mut envs = vec![
+                    ("WINEPREFIX".to_string(), data_path.join("pfx").into()),
+                    ("GAMEID".to_string(), "umu-default".into()),
+                    ("STORE".to_string(), "none".into()),
+                ];
+
+                if let Some(path) = proton_path {
+                    envs.push(("PROTONPATH".to_string(), path.into()));
+                }
+
+                envs
+            }
+            _ => vec![],
+        }
+    }
+}
+
+impl Display for Runner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Runner::Bundled => write!(f, "bundled"),
+            Runner::System => write!(f, "system"),
+            Runner::Proton { path } => write!(f, "proton:{}", path.display()),
+            Runner::Umu { proton_path: None } => write!(f, "umu"),
+            Runner::Umu {
+                proton_path: Some(path),
+            } => write!(f, "umu:{}", path.display()),
+            Runner::Custom { program, args } => {
+                write!(f, "custom:{}", Self::format_custom_command(program, args))
+            }
+        }
+    }
+}
+
+impl FromStr for Runner {
+    type Err = anyhow::Error;
+
+    /// Parse `bundled`, `system`, `proton:<path>`, `umu`, `umu:<proton path>` or
+    /// `custom:<program> <args>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = match s.split_once(':') {
+            Some((kind, value)) => (kind, Some(value.trim()).filter(|v| !v.is_empty())),
+            None => (s, None),
+        };
+
+        match (kind.trim().to_lowercase().as_str(), value) {
+            ("bundled", None) => Ok(Runner::Bundled),
+            ("system", None) => Ok(Runner::System),
+            ("proton", Some(path)) => Ok(Runner::Proton {
+                path: PathBuf::from(path),
+            }),
+            ("proton", None) => anyhow::bail!("The path of the Proton build is missing, use proton:<path>"),
+            ("umu", path) => Ok(Runner::Umu {
+                proton_path: path.map(PathBuf::from),
+            }),
+            ("custom", Some(command)) => Self::parse_custom_command(command),
+            ("custom", None) => anyhow::bail!("The program to use is missing, use custom:<program> <args>"),
+            _ => anyhow::bail!(
+                "Unknown runner '{}', expected bundled, system, proton:<path>, umu, umu:<path> or custom:<program> <args>",
+                s
+            ),
+        }
+    }
+}
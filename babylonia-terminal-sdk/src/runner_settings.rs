@@ -0,0 +1,240 @@
+use std::{fmt::Display, path::Path, str::FromStr};
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use crate::runner::Runner;
+
+/// The first version of wine with the Wayland driver
+static WAYLAND_MIN_VERSION: (u32, u32) = (9, 0);
+/// The first version of wine using NTSync without patches
+static NTSYNC_MIN_VERSION: (u32, u32) = (10, 16);
+
+/// How the runner is tuned, translated to the variables of the selected runner
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunnerSettings {
+    pub sync: Option<SyncMode>,
+    pub graphics_driver: Option<GraphicsDriver>,
+}
+
+impl RunnerSettings {
+    /// Refuse what the runner or the system can't do, `wine_version` is the output of
+    /// `wine --version`, it's not checked when it's unknown
+    pub fn check(&self, runner: &Runner, wine_version: Option<&str>) -> anyhow::Result<()> {
+        // Proton and umu-launcher don't use the wine of the launcher
+        let version = wine_version
+            .filter(|_| runner.uses_wine_env())
+            .and_then(|v| parse_wine_version(v).map(|parsed| (v, parsed)));
+
+        if self.sync == Some(SyncMode::Ntsync) {
+            if !Path::new("/dev/ntsync").exists() {
+                anyhow::bail!(
+                    "NTSync needs the ntsync kernel module (Linux 6.14 or newer), /dev/ntsync was not found"
+                );
+            }
+
+            if let Some((raw, parsed)) = version {
+                if parsed < NTSYNC_MIN_VERSION {
+                    anyhow::bail!(
+                        "NTSync needs wine {}.{} or newer, {} is used",
+                        NTSYNC_MIN_VERSION.0,
+                        NTSYNC_MIN_VERSION.1,
+                        raw.trim()
+                    );
+                }
+            }
+        }
+
+        if self.graphics_driver == Some(GraphicsDriver::Wayland) {
+            if std::env::var_os("WAYLAND_DISPLAY").is_none() {
+                anyhow::bail!("The Wayland driver can only be used in a Wayland session");
+            }
+
+            if let Some((raw, parsed)) = version {
+                if parsed < WAYLAND_MIN_VERSION {
+                    anyhow::bail!(
+                        "The Wayland driver needs wine {}.{} or newer, {} is used",
+                        WAYLAND_MIN_VERSION.0,
+                        WAYLAND_MIN_VERSION.1,
+                        raw.trim()
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The variables to set, or to remove when the value is none
+    pub fn get_envs(&self, runner: &Runner) -> Vec<(String, Option<String>)> {
+        let mut envs: Vec<(&str, Option<&str>)> = vec![];
+
+        if runner.uses_wine_env() {
+            // WINE_DISABLE_FAST_SYNC is the NTSync of wine-tkg and wine-ge
+            match self.sync {
+                Some(SyncMode::Wineserver) => envs.extend([
+                    ("WINEESYNC", Some("0")),
+                    ("WINEFSYNC", Some("0")),
+                    ("WINE_DISABLE_FAST_SYNC", Some("1")),
+                ]),
+                Some(SyncMode::Esync) => envs.extend([
+                    ("WINEESYNC", Some("1")),
+                    ("WINEFSYNC", Some("0")),
+                    ("WINE_DISABLE_FAST_SYNC", Some("1")),
+                ]),
+                // esync is used if fsync is not supported by the kernel
+                Some(SyncMode::Fsync) => envs.extend([
+                    ("WINEESYNC", Some("1")),
+                    ("WINEFSYNC", Some("1")),
+                    ("WINE_DISABLE_FAST_SYNC", Some("1")),
+                ]),
+                Some(SyncMode::Ntsync) => envs.extend([
+                    ("WINEESYNC", Some("0")),
+                    ("WINEFSYNC", Some("0")),
+                    ("WINE_DISABLE_FAST_SYNC", Some("0")),
+                ]),
+                None => {}
+            }
+
+            // wine uses the Wayland driver when it can't connect to X11
+            match self.graphics_driver {
+                Some(GraphicsDriver::X11) => envs.push(("WAYLAND_DISPLAY", None)),
+                Some(GraphicsDriver::Wayland) => envs.push(("DISPLAY", None)),
+                None => {}
+            }
+        } else {
+            match self.sync {
+                Some(SyncMode::Wineserver) => envs.extend([
+                    ("PROTON_NO_ESYNC", Some("1")),
+                    ("PROTON_NO_FSYNC", Some("1")),
+                    ("PROTON_USE_NTSYNC", Some("0")),
+                ]),
+                Some(SyncMode::Esync) => envs.extend([
+                    ("PROTON_NO_ESYNC", Some("0")),
+                    ("PROTON_NO_FSYNC", Some("1")),
+                    ("PROTON_USE_NTSYNC", Some("0")),
+                ]),
+                Some(SyncMode::Fsync) => envs.extend([
+                    ("PROTON_NO_ESYNC", Some("0")),
+                    ("PROTON_NO_FSYNC", Some("0")),
+                    ("PROTON_USE_NTSYNC", Some("0")),
+                ]),
+                Some(SyncMode::Ntsync) => envs.push(("PROTON_USE_NTSYNC", Some("1"))),
+                None => {}
+            }
+
+            match self.graphics_driver {
+                Some(GraphicsDriver::X11) => envs.push(("PROTON_ENABLE_WAYLAND", Some("0"))),
+                Some(GraphicsDriver::Wayland) => envs.push(("PROTON_ENABLE_WAYLAND", Some("1"))),
+                None => {}
+            }
+        }
+
+        envs.into_iter()
+            .map(|(name, value)| (name.to_string(), value.map(String::from)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SyncMode {
+    /// Without any of the others, the slowest
+    Wineserver,
+    Esync,
+    Fsync,
+    Ntsync,
+}
+
+impl SyncMode {
+    pub fn all() -> [SyncMode; 4] {
+        [
+            SyncMode::Wineserver,
+            SyncMode::Esync,
+            SyncMode::Fsync,
+            SyncMode::Ntsync,
+        ]
+    }
+}
+
+impl Display for SyncMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyncMode::Wineserver => write!(f, "wineserver"),
+            SyncMode::Esync => write!(f, "esync"),
+            SyncMode::Fsync => write!(f, "fsync"),
+            SyncMode::Ntsync => write!(f, "ntsync"),
+        }
+    }
+}
+
+impl FromStr for SyncMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "wineserver" | "none" => Ok(SyncMode::Wineserver),
+            "esync" => Ok(SyncMode::Esync),
+            "fsync" => Ok(SyncMode::Fsync),
+            "ntsync" => Ok(SyncMode::Ntsync),
+            _ => anyhow::bail!(
+                "Unknown synchronization '{}', expected wineserver, esync, fsync or ntsync",
+                s
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GraphicsDriver {
+    X11,
+    Wayland,
+}
+
+impl GraphicsDriver {
+    pub fn all() -> [GraphicsDriver; 2] {
+        [GraphicsDriver::X11, GraphicsDriver::Wayland]
+    }
+}
+
+impl Display for GraphicsDriver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphicsDriver::X11 => write!(f, "x11"),
+            GraphicsDriver::Wayland => write!(f, "wayland"),
+        }
+    }
+}
+
+impl FromStr for GraphicsDriver {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "x11" => Ok(GraphicsDriver::X11),
+            "wayland" => Ok(GraphicsDriver::Wayland),
+            _ => anyhow::bail!("Unknown graphics driver '{}', expected x11 or wayland", s),
+        }
+    }
+}
+
+/// Parse the major and minor version of `wine --version`, like `wine-9.0 (Staging)`
+fn parse_wine_version(version: &str) -> Option<(u32, u32)> {
+    let version = version
+        .trim()
+        .strip_prefix("wine-")
+        .unwrap_or(version.trim());
+    let number: String = version
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+
+    let mut parts = number.split('.');
+    let parsed = (
+        parts.next()?.parse().ok()?,
+        parts.next().and_then(|m| m.parse().ok()).unwrap_or(0),
+    );
+
+    debug!("wine version parsed as {:?}", parsed);
+    Some(parsed)
+}
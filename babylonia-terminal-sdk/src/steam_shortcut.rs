@@ -0,0 +1,312 @@
+use std::path::{Path, PathBuf};
+
+use log::debug;
+use tokio::fs::{copy, read, write};
+
+use crate::{
+    desktop_entry::ICON,
+    game_config::GameConfig,
+    utils::{
+        binary_vdf::{self, VdfMap, VdfValue},
+        get_game_name, write_atomic,
+    },
+};
+
+const FLATPAK_ID: &str = "com.valvesoftware.Steam";
+
+/// Added to the tags of the shortcut, to find it among the ones added by the user
+pub static SHORTCUT_TAG: &str = "Babylonia Terminal";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SteamInstall {
+    pub path: PathBuf,
+    /// The game is started with `flatpak-spawn --host` to get out of the sandbox
+    pub is_flatpak: bool,
+}
+
+impl SteamInstall {
+    /// The `BT_STEAM_CLIENT_PATH` variable replace the usual locations
+    pub fn find_all() -> Vec<SteamInstall> {
+        if let Ok(path) = std::env::var("BT_STEAM_CLIENT_PATH") {
+            let path = PathBuf::from(path);
+            let is_flatpak = path.components().any(|c| c.as_os_str() == FLATPAK_ID);
+            return vec![SteamInstall { path, is_flatpak }];
+        }
+
+        let home = dirs::home_dir().unwrap();
+        let locations = [
+            (home.join(".steam/steam"), false),
+            (home.join(".local/share/Steam"), false),
+            (
+                home.join(".var/app")
+                    .join(FLATPAK_ID)
+                    .join(".local/share/Steam"),
+                true,
+            ),
+        ];
+
+        let mut installs: Vec<SteamInstall> = vec![];
+        let mut real_paths = vec![];
+        for (path, is_flatpak) in locations {
+            // ~/.steam/steam is usually a link to ~/.local/share/Steam
+            let Ok(real_path) = path.canonicalize() else {
+                continue;
+            };
+
+            if !real_path.join("userdata").is_dir() || real_paths.contains(&real_path) {
+                continue;
+            }
+
+            debug!("Steam found in {:?}", path);
+            real_paths.push(real_path);
+            installs.push(SteamInstall { path, is_flatpak });
+        }
+
+        installs
+    }
+
+    /// One file for each user who logged in Steam, it doesn't always exist yet
+    pub fn get_shortcuts_files(&self) -> Vec<PathBuf> {
+        let Ok(entries) = std::fs::read_dir(self.path.join("userdata")) else {
+            return vec![];
+        };
+
+        entries
+            .flatten()
+            .filter(|e| {
+                let name = e.file_name().to_string_lossy().to_string();
+                name != "0" && name.chars().all(|c| c.is_ascii_digit())
+            })
+            .map(|e| e.path().join("config"))
+            .filter(|p| p.is_dir())
+            .map(|p| p.join("shortcuts.vdf"))
+            .collect()
+    }
+}
+
+/// Steam computes the id of a non-Steam game the same way, it's used to find its artworks
+pub fn get_app_id(exe: &str, name: &str) -> u32 {
+    crc32fast::hash(format!("{}{}", exe, name).as_bytes()) | 0x80000000
+}
+
+/// Add the game to every Steam user found, or update the shortcut if it's already there, and
+/// return how many users were updated. Steam needs to be restarted to see the change
+pub async fn add_shortcut(executable: &Path) -> anyhow::Result<usize> {
+    let installs = SteamInstall::find_all();
+    if installs.is_empty() {
+        anyhow::bail!(
+            "Steam was not found, install it or set BT_STEAM_CLIENT_PATH to its directory"
+        );
+    }
+
+    let icon = GameConfig::get_config_directory()
+        .await
+        .join("steam-icon.png");
+    write(&icon, ICON).await?;
+
+    let mut count = 0;
+    for install in installs {
+        let shortcut = get_shortcut(&install, executable, &icon);
+
+        for file in install.get_shortcuts_files() {
+            let mut root = read_shortcuts(&file).await?;
+            let shortcuts = get_shortcuts_mut(&mut root)?;
+
+            match shortcuts.iter_mut().find(|(_, s)| is_launcher_shortcut(s)) {
+                Some((_, VdfValue::Map(existing))) => {
+                    // the other values can be changed by the user in Steam
+                    for key in [
+                        "appid",
+                        "AppName",
+                        "Exe",
+                        "StartDir",
+                        "icon",
+                        "LaunchOptions",
+                    ] {
+                        if let Some(value) = binary_vdf::get(&shortcut, key) {
+                            binary_vdf::set(existing, key, value.clone());
+                        }
+                    }
+
+                    add_tag(existing);
+                }
+                _ => {
+                    let index = shortcuts.len().to_string().into_bytes();
+                    shortcuts.push((index, VdfValue::Map(shortcut.clone())));
+                }
+            }
+
+            write_shortcuts(&file, &root).await?;
+            debug!("Shortcut added to {:?}", file);
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+/// Return how many Steam users had the shortcut
+pub async fn remove_shortcut() -> anyhow::Result<usize> {
+    let mut count = 0;
+
+    for install in SteamInstall::find_all() {
+        for file in install.get_shortcuts_files() {
+            if !file.exists() {
+                continue;
+            }
+
+            let mut root = read_shortcuts(&file).await?;
+            let shortcuts = get_shortcuts_mut(&mut root)?;
+
+            let len = shortcuts.len();
+            shortcuts.retain(|(_, s)| !is_launcher_shortcut(s));
+            if shortcuts.len() == len {
+                continue;
+            }
+
+            // the keys are the indexes of the list
+            for (i, (key, _)) in shortcuts.iter_mut().enumerate() {
+                *key = i.to_string().into_bytes();
+            }
+
+            write_shortcuts(&file, &root).await?;
+            debug!("Shortcut removed from {:?}", file);
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+fn get_shortcut(install: &SteamInstall, executable: &Path, icon: &Path) -> VdfMap {
+    let name = get_game_name();
+    let executable_arg = format!("\"{}\"", executable.to_string_lossy());
+
+    let (exe, launch_options) = if install.is_flatpak {
+        (
+            "\"/usr/bin/flatpak-spawn\"".to_string(),
+            format!("--host {}", executable_arg),
+        )
+    } else {
+        (executable_arg, String::new())
+    };
+
+    let start_dir = executable
+        .parent()
+        .map(|p| format!("\"{}\"", p.to_string_lossy()))
+        .unwrap_or_default();
+
+    let string = VdfValue::string;
+
+    vec![
+        ("appid".into(), VdfValue::Int(get_app_id(&exe, &name))),
+        ("AppName".into(), string(&name)),
+        ("Exe".into(), string(&exe)),
+        ("StartDir".into(), string(&start_dir)),
+        ("icon".into(), string(&icon.to_string_lossy())),
+        ("ShortcutPath".into(), string("")),
+        ("LaunchOptions".into(), string(&launch_options)),
+        ("IsHidden".into(), VdfValue::Int(0)),
+        ("AllowDesktopConfig".into(), VdfValue::Int(1)),
+        ("AllowOverlay".into(), VdfValue::Int(1)),
+        ("OpenVR".into(), VdfValue::Int(0)),
+        ("Devkit".into(), VdfValue::Int(0)),
+        ("DevkitGameID".into(), string("")),
+        ("DevkitOverrideAppID".into(), VdfValue::Int(0)),
+        ("LastPlayTime".into(), VdfValue::Int(0)),
+        ("FlatpakAppID".into(), string("")),
+        (
+            "tags".into(),
+            VdfValue::Map(vec![("0".into(), string(SHORTCUT_TAG))]),
+        ),
+    ]
+}
+
+// the shortcut is found by its tag, so it's still updated if the launcher was moved, the ones
+// added before the tag are found by the launcher they start
+fn is_launcher_shortcut(shortcut: &VdfValue) -> bool {
+    let Some(shortcut) = shortcut.as_map() else {
+        return false;
+    };
+
+    if get_tags(shortcut).any(|tag| tag == SHORTCUT_TAG.as_bytes()) {
+        return true;
+    }
+
+    let Some(launcher) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.file_name()?.to_string_lossy().to_string()))
+    else {
+        return false;
+    };
+
+    let is_game = binary_vdf::get(shortcut, "AppName")
+        .and_then(|name| name.as_str())
+        .is_some_and(|name| name == get_game_name());
+    let starts_launcher = ["Exe", "LaunchOptions"].iter().any(|key| {
+        binary_vdf::get(shortcut, key)
+            .and_then(|value| value.as_str())
+            .is_some_and(|value| {
+                value
+                    .split('"')
+                    .any(|part| part.trim().ends_with(&format!("/{}", launcher)))
+            })
+    });
+
+    is_game && starts_launcher
+}
+
+fn get_tags(shortcut: &VdfMap) -> impl Iterator<Item = &[u8]> {
+    binary_vdf::get(shortcut, "tags")
+        .and_then(|tags| tags.as_map())
+        .into_iter()
+        .flatten()
+        .filter_map(|(_, tag)| match tag {
+            VdfValue::String(tag) => Some(tag.as_slice()),
+            _ => None,
+        })
+}
+
+// the tags of the user are kept
+fn add_tag(shortcut: &mut VdfMap) {
+    if get_tags(shortcut).any(|tag| tag == SHORTCUT_TAG.as_bytes()) {
+        return;
+    }
+
+    if !matches!(binary_vdf::get(shortcut, "tags"), Some(VdfValue::Map(_))) {
+        binary_vdf::set(shortcut, "tags", VdfValue::Map(vec![]));
+    }
+
+    if let Some(VdfValue::Map(tags)) = binary_vdf::get_mut(shortcut, "tags") {
+        let index = tags.len().to_string().into_bytes();
+        tags.push((index, VdfValue::string(SHORTCUT_TAG)));
+    }
+}
+
+async fn read_shortcuts(file: &Path) -> anyhow::Result<VdfMap> {
+    if !file.exists() {
+        return Ok(vec![("shortcuts".into(), VdfValue::Map(vec![]))]);
+    }
+
+    binary_vdf::parse(&read(file).await?)
+        .map_err(|e| anyhow::anyhow!("Failed to read {:?} : {}", file, e))
+}
+
+fn get_shortcuts_mut(root: &mut VdfMap) -> anyhow::Result<&mut VdfMap> {
+    if binary_vdf::get(root, "shortcuts").is_none() {
+        root.push(("shortcuts".into(), VdfValue::Map(vec![])));
+    }
+
+    binary_vdf::get_mut(root, "shortcuts")
+        .and_then(|s| s.as_map_mut())
+        .ok_or_else(|| anyhow::anyhow!("The shortcuts of Steam are not a list"))
+}
+
+async fn write_shortcuts(file: &Path, root: &VdfMap) -> anyhow::Result<()> {
+    if file.exists() {
+        copy(file, file.with_extension("vdf.bak")).await?;
+    }
+
+    write_atomic(file, binary_vdf::to_bytes(root)).await?;
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::game_config::RenderApi;
+
+/// The command-line arguments of the Unity player, passed to the game before the other arguments
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UnityPlayerSettings {
+    pub window_mode: Option<WindowMode>,
+    pub resolution: Option<Resolution>,
+    /// Starting from 1
+    pub monitor: Option<u32>,
+    /// A window without decoration, only with the windowed mode
+    pub popup_window: bool,
+}
+
+impl UnityPlayerSettings {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.monitor == Some(0) {
+            anyhow::bail!("The monitors start from 1");
+        }
+
+        if self.popup_window && self.window_mode.is_some_and(|m| m != WindowMode::Windowed) {
+            anyhow::bail!("The popup window can only be used with the windowed mode");
+        }
+
+        Ok(())
+    }
+
+    pub fn get_args(&self, render_api: RenderApi) -> Vec<String> {
+        let mut args: Vec<String> = vec![];
+
+        if let Some(argument) = render_api.get_game_argument() {
+            args.push(argument.to_string());
+        }
+
+        let window_args: &[&str] = match self.window_mode {
+            Some(WindowMode::Fullscreen) => {
+                &["-screen-fullscreen", "1", "-window-mode", "exclusive"]
+            }
+            Some(WindowMode::Borderless) => {
+                &["-screen-fullscreen", "1", "-window-mode", "borderless"]
+            }
+            Some(WindowMode::Windowed) => &["-screen-fullscreen", "0"],
+            None => &[],
+        };
+        args.extend(window_args.iter().map(|a| a.to_string()));
+
+        if let Some(resolution) = self.resolution {
+            args.push("-screen-width".to_string());
+            args.push(resolution.width.to_string());
+            args.push("-screen-height".to_string());
+            args.push(resolution.height.to_string());
+        }
+
+        if let Some(monitor) = self.monitor {
+            args.push("-monitor".to_string());
+            args.push(monitor.to_string());
+        }
+
+        if self.popup_window {
+            args.push("-popupwindow".to_string());
+        }
+
+        args
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindowMode {
+    Fullscreen,
+    /// A fullscreen window, faster to switch to another window
+    Borderless,
+    Windowed,
+}
+
+impl WindowMode {
+    pub fn all() -> [WindowMode; 3] {
+        [
+            WindowMode::Fullscreen,
+            WindowMode::Borderless,
+            WindowMode::Windowed,
+        ]
+    }
+}
+
+impl Display for WindowMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WindowMode::Fullscreen => write!(f, "fullscreen"),
+            WindowMode::Borderless => write!(f, "borderless"),
+            WindowMode::Windowed => write!(f, "windowed"),
+        }
+    }
+}
+
+impl FromStr for WindowMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fullscreen" => Ok(WindowMode::Fullscreen),
+            "borderless" => Ok(WindowMode::Borderless),
+            "windowed" => Ok(WindowMode::Windowed),
+            _ => anyhow::bail!(
+                "Unknown window mode '{}', expected fullscreen, borderless or windowed",
+                s
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Display for Resolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+impl FromStr for Resolution {
+    type Err = anyhow::Error;
+
+    /// Parse a resolution like `1920x1080`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let size = s.trim().split_once('x').and_then(|(w, h)| {
+            Some((w.trim().parse::<u32>().ok()?, h.trim().parse::<u32>().ok()?))
+        });
+
+        match size {
+            Some((width, height)) if width > 0 && height > 0 => Ok(Resolution { width, height }),
+            _ => anyhow::bail!("Invalid resolution '{}', expected WIDTHxHEIGHT", s),
+        }
+    }
+}
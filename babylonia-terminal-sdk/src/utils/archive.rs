@@ -0,0 +1,366 @@
+use std::{
+    ffi::OsString,
+    fs::{create_dir_all, remove_dir_all, remove_file, rename, File},
+    io::{self, Read},
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use downloader::progress::Reporter;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use log::debug;
+use tar::{Archive, Builder};
+use xz2::read::XzDecoder;
+use zip::ZipArchive;
+
+// only report every MiB, the reporters are sending a message to the UI on each call
+const PROGRESS_STEP: u64 = 1024 * 1024;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ArchiveFormat {
+    TarGz,
+    TarXz,
+    TarZst,
+    Zip,
+}
+
+impl ArchiveFormat {
+    pub fn detect(file: &Path) -> anyhow::Result<Self> {
+        let mut magic = Vec::with_capacity(6);
+        File::open(file)?.take(6).read_to_end(&mut magic)?;
+
+        match Self::from_magic(&magic) {
+            Some(format) => Ok(format),
+            None => anyhow::bail!(
+                "Unsupported archive format for {}, expected a tar.gz, tar.xz, tar.zst or zip file",
+                file.to_str().unwrap()
+            ),
+        }
+    }
+
+    fn from_magic(magic: &[u8]) -> Option<Self> {
+        if magic.starts_with(&[0x1f, 0x8b]) {
+            Some(ArchiveFormat::TarGz)
+        } else if magic.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(ArchiveFormat::TarXz)
+        } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(ArchiveFormat::TarZst)
+        } else if magic.starts_with(&[b'P', b'K', 0x03, 0x04]) {
+            Some(ArchiveFormat::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+/// Extract `file` into `output_dir` and return the root of the extracted content,
+/// which is the top-level directory of the archive if it has only one, `output_dir` otherwise.
+pub async fn extract<P: Reporter + 'static>(
+    file: PathBuf,
+    output_dir: PathBuf,
+    progress: Option<Arc<P>>,
+) -> anyhow::Result<PathBuf> {
+    tokio::task::spawn_blocking(move || extract_blocking(&file, &output_dir, progress)).await?
+}
+
+/// Extract a downloaded component archive to `new_directory_name` and delete the archive.
+///
+/// The content is unpacked in a staging directory first, so it doesn't matter how the top-level
+/// directory of the archive is named.
+pub async fn extract_component<P: Reporter + 'static>(
+    file: PathBuf,
+    new_directory_name: PathBuf,
+    progress: Option<Arc<P>>,
+) -> anyhow::Result<()> {
+    tokio::task::spawn_blocking(move || -> anyhow::Result<()> {
+        replace_dir_blocking(&file, &new_directory_name, progress)?;
+        remove_file(&file)?;
+
+        Ok(())
+    })
+    .await??;
+
+    Ok(())
+}
+
+/// Same as [`extract_component`] but the archive is kept, the old directory is only removed
+/// once the archive is fully extracted.
+pub async fn extract_and_replace<P: Reporter + 'static>(
+    file: PathBuf,
+    new_directory_name: PathBuf,
+    progress: Option<Arc<P>>,
+) -> anyhow::Result<()> {
+    tokio::task::spawn_blocking(move || replace_dir_blocking(&file, &new_directory_name, progress))
+        .await?
+}
+
+/// Create a tar.gz of `dir`, with the directory as the only top-level entry.
+///
+/// The symlinks are stored as they are, a wine prefix has links to `/` in `dosdevices`.
+pub async fn compress_dir(dir: PathBuf, output: PathBuf) -> anyhow::Result<()> {
+    tokio::task::spawn_blocking(move || -> anyhow::Result<()> {
+        debug!("Compressing {:?} to {:?}", dir, output);
+
+        let tmp_output = output.with_extension("part");
+        let encoder = GzEncoder::new(File::create(&tmp_output)?, Compression::default());
+
+        let mut builder = Builder::new(encoder);
+        builder.follow_symlinks(false);
+        builder.append_dir_all(dir.file_name().unwrap(), &dir)?;
+        builder.into_inner()?.finish()?;
+
+        rename(&tmp_output, &output)?;
+
+        Ok(())
+    })
+    .await??;
+
+    Ok(())
+}
+
+fn replace_dir_blocking<P: Reporter + 'static>(
+    file: &Path,
+    new_directory_name: &Path,
+    progress: Option<Arc<P>>,
+) -> anyhow::Result<()> {
+    let parent = new_directory_name
+        .parent()
+        .expect("Failed to get the parent directory of the component")
+        .to_path_buf();
+    let staging_dir = parent.join(format!(
+        ".{}-extracting",
+        new_directory_name.file_name().unwrap().to_str().unwrap()
+    ));
+
+    if staging_dir.exists() {
+        remove_dir_all(&staging_dir)?;
+    }
+
+    let root = extract_blocking(file, &staging_dir, progress)?;
+
+    if new_directory_name.exists() {
+        debug!("Removing old directory at {:?}", new_directory_name);
+        remove_dir_all(new_directory_name)?;
+    }
+
+    rename(&root, new_directory_name)?;
+    if root != staging_dir {
+        remove_dir_all(&staging_dir)?;
+    }
+
+    Ok(())
+}
+
+fn extract_blocking<P: Reporter + 'static>(
+    file: &Path,
+    output_dir: &Path,
+    progress: Option<Arc<P>>,
+) -> anyhow::Result<PathBuf> {
+    let format = ArchiveFormat::detect(file)?;
+    debug!("Extracting {:?} ({:?}) to {:?}", file, format, output_dir);
+
+    create_dir_all(output_dir)?;
+
+    if let Some(p) = &progress {
+        p.setup(
+            Some(file.metadata()?.len()),
+            &format!("Extracting {}", file.file_name().unwrap().to_str().unwrap()),
+        );
+    }
+
+    let reader = ProgressReader::new(File::open(file)?, progress.clone());
+    let mut top_level = TopLevel::default();
+
+    match format {
+        ArchiveFormat::TarGz => unpack_tar(GzDecoder::new(reader), output_dir, &mut top_level)?,
+        ArchiveFormat::TarXz => unpack_tar(XzDecoder::new(reader), output_dir, &mut top_level)?,
+        ArchiveFormat::TarZst => unpack_tar(
+            zstd::stream::read::Decoder::new(reader)?,
+            output_dir,
+            &mut top_level,
+        )?,
+        ArchiveFormat::Zip => {
+            drop(reader);
+            unpack_zip(file, output_dir, progress.clone(), &mut top_level)?
+        }
+    }
+
+    if let Some(p) = &progress {
+        p.done();
+    }
+
+    Ok(top_level.root(output_dir))
+}
+
+fn unpack_tar<R: Read>(
+    reader: R,
+    output_dir: &Path,
+    top_level: &mut TopLevel,
+) -> anyhow::Result<()> {
+    let mut archive = Archive::new(reader);
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_type = entry.header().entry_type();
+
+        if entry_type.is_pax_global_extensions()
+            || entry_type.is_pax_local_extensions()
+            || entry_type.is_gnu_longname()
+            || entry_type.is_gnu_longlink()
+        {
+            continue;
+        }
+
+        let path = entry.path()?.into_owned();
+        check_path(&path)?;
+        top_level.add(&path, entry_type.is_dir());
+
+        // unpack_in also refuse to write through a symlink pointing outside of the directory
+        if !entry.unpack_in(output_dir)? {
+            anyhow::bail!(
+                "Refusing to extract {:?}, it's pointing outside of the output directory",
+                path
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn unpack_zip<P: Reporter + 'static>(
+    file: &Path,
+    output_dir: &Path,
+    progress: Option<Arc<P>>,
+    top_level: &mut TopLevel,
+) -> anyhow::Result<()> {
+    let mut archive = ZipArchive::new(File::open(file)?)?;
+    let mut read: u64 = 0;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+
+        let path = match entry.enclosed_name() {
+            Some(p) => p,
+            None => anyhow::bail!(
+                "Refusing to extract {}, it's pointing outside of the output directory",
+                entry.name()
+            ),
+        };
+        check_path(&path)?;
+        top_level.add(&path, entry.is_dir());
+
+        let output_path = output_dir.join(&path);
+        if entry.is_dir() {
+            create_dir_all(&output_path)?;
+        } else {
+            if let Some(parent) = output_path.parent() {
+                create_dir_all(parent)?;
+            }
+            let mut output_file = File::create(&output_path)?;
+            io::copy(&mut entry, &mut output_file)?;
+
+            #[cfg(unix)]
+            if let Some(mode) = entry.unix_mode() {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(&output_path, std::fs::Permissions::from_mode(mode))?;
+            }
+        }
+
+        read += entry.compressed_size();
+        if let Some(p) = &progress {
+            p.progress(read);
+        }
+    }
+
+    Ok(())
+}
+
+fn check_path(path: &Path) -> anyhow::Result<()> {
+    if path.components().any(|c| {
+        matches!(
+            c,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_)
+        )
+    }) {
+        anyhow::bail!(
+            "Refusing to extract {:?}, it's pointing outside of the output directory",
+            path
+        );
+    }
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct TopLevel {
+    name: Option<OsString>,
+    is_dir: bool,
+    is_unique: bool,
+}
+
+impl TopLevel {
+    fn add(&mut self, path: &Path, is_dir: bool) {
+        let mut components = path.components().filter_map(|c| match c {
+            Component::Normal(name) => Some(name.to_os_string()),
+            _ => None,
+        });
+
+        let first = match components.next() {
+            Some(f) => f,
+            None => return, // "./"
+        };
+        // a nested entry means the first component is a directory
+        let is_dir = is_dir || components.next().is_some();
+
+        match &self.name {
+            None => {
+                self.name = Some(first);
+                self.is_dir = is_dir;
+                self.is_unique = true;
+            }
+            Some(name) if *name == first => self.is_dir |= is_dir,
+            Some(_) => self.is_unique = false,
+        }
+    }
+
+    fn root(&self, output_dir: &Path) -> PathBuf {
+        match &self.name {
+            Some(name) if self.is_unique && self.is_dir => output_dir.join(name),
+            _ => output_dir.to_path_buf(),
+        }
+    }
+}
+
+struct ProgressReader<R: Read, P: Reporter + 'static> {
+    inner: R,
+    progress: Option<Arc<P>>,
+    read: u64,
+    last_report: u64,
+}
+
+impl<R: Read, P: Reporter + 'static> ProgressReader<R, P> {
+    fn new(inner: R, progress: Option<Arc<P>>) -> Self {
+        Self {
+            inner,
+            progress,
+            read: 0,
+            last_report: 0,
+        }
+    }
+}
+
+impl<R: Read, P: Reporter + 'static> Read for ProgressReader<R, P> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+
+        if let Some(p) = &self.progress {
+            if n == 0 || self.read - self.last_report >= PROGRESS_STEP {
+                p.progress(self.read);
+                self.last_report = self.read;
+            }
+        }
+
+        Ok(n)
+    }
+}
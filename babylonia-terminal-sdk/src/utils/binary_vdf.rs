@@ -0,0 +1,244 @@
+const TYPE_MAP: u8 = 0x00;
+const TYPE_STRING: u8 = 0x01;
+const TYPE_INT: u8 = 0x02;
+const TYPE_FLOAT: u8 = 0x03;
+const TYPE_UINT64: u8 = 0x07;
+const TYPE_END: u8 = 0x08;
+
+/// The order of the keys is kept, Steam uses it for the lists. The keys and the strings are kept
+/// as bytes, they are not always UTF-8 and are written back as they were
+pub type VdfMap = Vec<(Vec<u8>, VdfValue)>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VdfValue {
+    Map(VdfMap),
+    String(Vec<u8>),
+    Int(u32),
+    Float(f32),
+    UInt64(u64),
+}
+
+impl VdfValue {
+    pub fn string(value: &str) -> Self {
+        VdfValue::String(value.as_bytes().to_vec())
+    }
+
+    pub fn as_map(&self) -> Option<&VdfMap> {
+        match self {
+            VdfValue::Map(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn as_map_mut(&mut self) -> Option<&mut VdfMap> {
+        match self {
+            VdfValue::Map(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// `None` if it's not a string or not UTF-8
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            VdfValue::String(value) => std::str::from_utf8(value).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Steam doesn't always use the same case for the keys, like `AppName` and `appname`
+pub fn get<'a>(map: &'a VdfMap, key: &str) -> Option<&'a VdfValue> {
+    map.iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key.as_bytes()))
+        .map(|(_, v)| v)
+}
+
+pub fn get_mut<'a>(map: &'a mut VdfMap, key: &str) -> Option<&'a mut VdfValue> {
+    map.iter_mut()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key.as_bytes()))
+        .map(|(_, v)| v)
+}
+
+/// Replace the value of the key, or add it at the end of the map
+pub fn set(map: &mut VdfMap, key: &str, value: VdfValue) {
+    match get_mut(map, key) {
+        Some(v) => *v = value,
+        None => map.push((key.into(), value)),
+    }
+}
+
+/// Parse the binary KeyValues format used by Steam, like `shortcuts.vdf`
+pub fn parse(data: &[u8]) -> anyhow::Result<VdfMap> {
+    let mut reader = Reader { data, pos: 0 };
+    reader.read_map()
+}
+
+pub fn to_bytes(map: &VdfMap) -> Vec<u8> {
+    let mut out = vec![];
+    write_map(map, &mut out);
+    out
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn read_bytes(&mut self, len: usize) -> anyhow::Result<&[u8]> {
+        if self.pos + len > self.data.len() {
+            anyhow::bail!("Unexpected end of the VDF file");
+        }
+
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn read_byte(&mut self) -> anyhow::Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_string(&mut self) -> anyhow::Result<Vec<u8>> {
+        let Some(len) = self.data[self.pos..].iter().position(|b| *b == 0) else {
+            anyhow::bail!("Unterminated string in the VDF file");
+        };
+
+        let value = self.data[self.pos..self.pos + len].to_vec();
+        self.pos += len + 1;
+        Ok(value)
+    }
+
+    fn read_map(&mut self) -> anyhow::Result<VdfMap> {
+        let mut map = vec![];
+
+        loop {
+            let kind = self.read_byte()?;
+            if kind == TYPE_END {
+                return Ok(map);
+            }
+
+            let key = self.read_string()?;
+            let value = match kind {
+                TYPE_MAP => VdfValue::Map(self.read_map()?),
+                TYPE_STRING => VdfValue::String(self.read_string()?),
+                TYPE_INT => VdfValue::Int(u32::from_le_bytes(self.read_bytes(4)?.try_into()?)),
+                TYPE_FLOAT => VdfValue::Float(f32::from_le_bytes(self.read_bytes(4)?.try_into()?)),
+                TYPE_UINT64 => {
+                    VdfValue::UInt64(u64::from_le_bytes(self.read_bytes(8)?.try_into()?))
+                }
+                _ => anyhow::bail!(
+                    "Unknown type {:#04x} in the VDF file for '{}'",
+                    kind,
+                    String::from_utf8_lossy(&key)
+                ),
+            };
+
+            map.push((key, value));
+        }
+    }
+}
+
+fn write_map(map: &VdfMap, out: &mut Vec<u8>) {
+    for (key, value) in map {
+        let kind = match value {
+            VdfValue::Map(_) => TYPE_MAP,
+            VdfValue::String(_) => TYPE_STRING,
+            VdfValue::Int(_) => TYPE_INT,
+            VdfValue::Float(_) => TYPE_FLOAT,
+            VdfValue::UInt64(_) => TYPE_UINT64,
+        };
+
+        out.push(kind);
+        write_string(key, out);
+
+        match value {
+            VdfValue::Map(map) => write_map(map, out),
+            VdfValue::String(value) => write_string(value, out),
+            VdfValue::Int(value) => out.extend(value.to_le_bytes()),
+            VdfValue::Float(value) => out.extend(value.to_le_bytes()),
+            VdfValue::UInt64(value) => out.extend(value.to_le_bytes()),
+        }
+    }
+
+    out.push(TYPE_END);
+}
+
+fn write_string(value: &[u8], out: &mut Vec<u8>) {
+    out.extend(value);
+    out.push(0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // written like Steam, the first shortcut has a Latin-1 name, path and tag
+    static SHORTCUTS: &[u8] = include_bytes!("../../tests/data/shortcuts.vdf");
+
+    fn get_shortcut(root: &VdfMap, index: &str) -> VdfMap {
+        let shortcuts = get(root, "shortcuts").and_then(|s| s.as_map()).unwrap();
+        get(shortcuts, index)
+            .and_then(|s| s.as_map())
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn round_trip() {
+        let root = parse(SHORTCUTS).unwrap();
+        assert_eq!(to_bytes(&root), SHORTCUTS);
+    }
+
+    #[test]
+    fn keep_non_utf8_strings() {
+        let shortcut = get_shortcut(&parse(SHORTCUTS).unwrap(), "0");
+
+        let name = get(&shortcut, "appname").unwrap();
+        assert_eq!(name, &VdfValue::String(b"Caf\xe9 Racer".to_vec()));
+        assert_eq!(name.as_str(), None);
+        assert_eq!(get(&shortcut, "appid"), Some(&VdfValue::Int(0x9c4b2f1e)));
+    }
+
+    #[test]
+    fn edit() {
+        let mut root = parse(SHORTCUTS).unwrap();
+        let shortcuts = get_mut(&mut root, "shortcuts")
+            .and_then(|s| s.as_map_mut())
+            .unwrap();
+        let shortcut = get_mut(shortcuts, "1")
+            .and_then(|s| s.as_map_mut())
+            .unwrap();
+
+        set(shortcut, "LaunchOptions", VdfValue::string(""));
+        set(shortcut, "LastPlayTime", VdfValue::Int(1700000000));
+        set(shortcut, "NewKey", VdfValue::UInt64(u64::MAX));
+
+        let root = parse(&to_bytes(&root)).unwrap();
+        let shortcut = get_shortcut(&root, "1");
+        assert_eq!(
+            get(&shortcut, "LaunchOptions").and_then(|v| v.as_str()),
+            Some("")
+        );
+        assert_eq!(
+            get(&shortcut, "LastPlayTime"),
+            Some(&VdfValue::Int(1700000000))
+        );
+        assert_eq!(
+            shortcut.last(),
+            Some(&("NewKey".into(), VdfValue::UInt64(u64::MAX)))
+        );
+
+        // the other shortcut is not changed
+        assert_eq!(
+            get_shortcut(&root, "0"),
+            get_shortcut(&parse(SHORTCUTS).unwrap(), "0")
+        );
+    }
+
+    #[test]
+    fn truncated_file() {
+        assert!(parse(&SHORTCUTS[..SHORTCUTS.len() - 2]).is_err());
+        assert!(parse(&SHORTCUTS[..20]).is_err());
+    }
+}
@@ -98,5 +98,24 @@ pub trait GithubRequester {
         Ok(release.unwrap().to_owned())
     }
 
+    #[allow(async_fn_in_trait)]
+    async fn get_github_release_index(
+        user: &str,
+        repo_name: &str,
+        tag_name: &str,
+    ) -> anyhow::Result<usize> {
+        let releases = Self::get_github_releases(user, repo_name).await?;
+
+        match releases.iter().position(|r| r.tag_name == tag_name) {
+            Some(index) => Ok(index),
+            None => anyhow::bail!("Release {} not found in {}/{}", tag_name, user, repo_name),
+        }
+    }
+
     fn set_github_release_index(&mut self, new_release_index: usize);
+
+    /// The tag of the release that was downloaded, if any
+    fn get_github_release_tag(&self) -> Option<String> {
+        None
+    }
 }
@@ -1,12 +1,20 @@
-use std::io;
+use std::{io, path::Path};
 
 use log::debug;
-use tokio::fs::remove_dir_all;
+use tokio::{
+    fs::{remove_dir_all, rename, File},
+    io::AsyncWriteExt,
+};
 
 use crate::game_config::GameConfig;
 
+pub mod archive;
+pub mod binary_vdf;
 pub mod github_requester;
 pub mod kuro_prod_api;
+pub mod prefix_registry;
+pub mod registry_file;
+pub mod winetricks;
 
 pub fn get_game_name() -> String {
     concat!("P", "G", "R").to_string()
@@ -23,3 +31,15 @@ pub async fn remove_setup() -> io::Result<()> {
 
     remove_dir_all(config_dir).await
 }
+
+/// Write to a temporary file next to `path` then rename it, so `path` is never left half written
+pub async fn write_atomic(path: &Path, content: impl AsRef<[u8]>) -> io::Result<()> {
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+
+    let mut file = File::create(&temp_path).await?;
+    file.write_all(content.as_ref()).await?;
+    file.sync_all().await?;
+
+    rename(&temp_path, path).await
+}
@@ -0,0 +1,135 @@
+use std::process::Command;
+
+use log::debug;
+use wincompatlib::wine::Wine;
+
+pub static DLL_OVERRIDES_KEY: &str = r"HKEY_CURRENT_USER\Software\Wine\DllOverrides";
+pub static DESKTOP_KEY: &str = r"HKEY_CURRENT_USER\Control Panel\Desktop";
+pub static EXPLORER_KEY: &str = r"HKEY_CURRENT_USER\Software\Wine\Explorer";
+pub static DESKTOPS_KEY: &str = r"HKEY_CURRENT_USER\Software\Wine\Explorer\Desktops";
+
+// the name of the desktop used by the virtual desktop
+static VIRTUAL_DESKTOP_NAME: &str = "Default";
+
+pub fn set_dll_override(wine: &Wine, dll: &str, mode: &str) -> anyhow::Result<()> {
+    reg(
+        wine,
+        &["add", DLL_OVERRIDES_KEY, "/v", dll, "/d", mode, "/f"],
+    )
+}
+
+pub fn remove_dll_override(wine: &Wine, dll: &str) -> anyhow::Result<()> {
+    delete_value(wine, DLL_OVERRIDES_KEY, dll)
+}
+
+/// Get the mode of a dll override, `None` when the dll isn't overridden
+pub fn get_dll_override(wine: &Wine, dll: &str) -> anyhow::Result<Option<String>> {
+    // reg fails when the value doesn't exist
+    let output = match run(wine, "reg", &["query", DLL_OVERRIDES_KEY, "/v", dll]) {
+        Ok(output) => output,
+        Err(e) => {
+            debug!("{}", e);
+            return Ok(None);
+        }
+    };
+
+    // the value is printed like `    d3d11    REG_SZ    native`
+    Ok(output.lines().find_map(|line| {
+        let (name, mode) = line.trim().split_once("REG_SZ")?;
+        if name.trim().eq_ignore_ascii_case(dll) {
+            Some(mode.trim().to_string())
+        } else {
+            None
+        }
+    }))
+}
+
+pub fn set_windows_version(wine: &Wine, version: &str) -> anyhow::Result<()> {
+    // the version is stored in many keys, winecfg knows them
+    run(wine, "winecfg", &["-v", version])?;
+    Ok(())
+}
+
+pub fn set_dpi(wine: &Wine, dpi: u32) -> anyhow::Result<()> {
+    reg(
+        wine,
+        &[
+            "add",
+            DESKTOP_KEY,
+            "/v",
+            "LogPixels",
+            "/t",
+            "REG_DWORD",
+            "/d",
+            &dpi.to_string(),
+            "/f",
+        ],
+    )
+}
+
+pub fn set_virtual_desktop(wine: &Wine, width: u32, height: u32) -> anyhow::Result<()> {
+    reg(
+        wine,
+        &[
+            "add",
+            EXPLORER_KEY,
+            "/v",
+            "Desktop",
+            "/d",
+            VIRTUAL_DESKTOP_NAME,
+            "/f",
+        ],
+    )?;
+    reg(
+        wine,
+        &[
+            "add",
+            DESKTOPS_KEY,
+            "/v",
+            VIRTUAL_DESKTOP_NAME,
+            "/d",
+            &format!("{}x{}", width, height),
+            "/f",
+        ],
+    )
+}
+
+pub fn remove_virtual_desktop(wine: &Wine) -> anyhow::Result<()> {
+    delete_value(wine, EXPLORER_KEY, "Desktop")
+}
+
+// reg fails when the value doesn't exist, which is fine here
+fn delete_value(wine: &Wine, key: &str, value: &str) -> anyhow::Result<()> {
+    if let Err(e) = reg(wine, &["delete", key, "/v", value, "/f"]) {
+        debug!("{}", e);
+    }
+
+    Ok(())
+}
+
+fn reg(wine: &Wine, args: &[&str]) -> anyhow::Result<()> {
+    run(wine, "reg", args)?;
+    Ok(())
+}
+
+fn run(wine: &Wine, program: &str, args: &[&str]) -> anyhow::Result<String> {
+    debug!("wine {} {}", program, args.join(" "));
+
+    let output = Command::new(&wine.binary)
+        .arg(program)
+        .args(args)
+        .envs(wine.get_envs())
+        .output()?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "wine {} {} failed ({}) : {}",
+            program,
+            args.join(" "),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
@@ -0,0 +1,378 @@
+use std::{fmt::Display, path::Path};
+
+use tokio::fs::{copy, read_to_string};
+
+use super::write_atomic;
+
+/// A registry file of a wine prefix, like `user.reg`, edited as text so what's not changed is
+/// written back as it was
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryFile {
+    lines: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryValue {
+    pub name: String,
+    pub data: RegistryData,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryData {
+    String(String),
+    Dword(u32),
+    /// `hex:` is REG_BINARY, `hex(b):` is REG_QWORD...
+    Hex {
+        kind: u32,
+        bytes: Vec<u8>,
+    },
+    /// What is not parsed, like `str(2):"..."`
+    Raw(String),
+}
+
+pub const REG_BINARY: u32 = 3;
+
+impl RegistryFile {
+    pub async fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = read_to_string(path)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to read {:?} : {}", path, e))?;
+
+        Ok(Self::parse(&content))
+    }
+
+    /// The previous file is kept with the `.bak` extension added
+    pub async fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if path.exists() {
+            let mut backup = path.as_os_str().to_owned();
+            backup.push(".bak");
+            copy(path, backup).await?;
+        }
+
+        write_atomic(path, self.to_string()).await?;
+        Ok(())
+    }
+
+    pub fn parse(content: &str) -> Self {
+        RegistryFile {
+            lines: content.lines().map(String::from).collect(),
+        }
+    }
+
+    pub fn has_key(&self, key: &str) -> bool {
+        self.find_key(key).is_some()
+    }
+
+    /// Empty if the key doesn't exist
+    pub fn get_values(&self, key: &str) -> Vec<RegistryValue> {
+        self.get_entries(key)
+            .into_iter()
+            .filter_map(|(start, end)| parse_value(&self.lines[start..end].join("\n")))
+            .collect()
+    }
+
+    /// Replace the value if it's already set, the key needs to exist
+    pub fn set_value(&mut self, key: &str, name: &str, data: &RegistryData) -> anyhow::Result<()> {
+        let Some(header) = self.find_key(key) else {
+            anyhow::bail!("The registry key '{}' doesn't exist", key);
+        };
+
+        let line = format!("\"{}\"={}", escape(name), data);
+
+        let existing = self.get_entries(key).into_iter().find(|(start, end)| {
+            parse_value(&self.lines[*start..*end].join("\n")).is_some_and(|v| v.name == name)
+        });
+
+        match existing {
+            Some((start, end)) => {
+                self.lines.splice(start..end, [line]);
+            }
+            None => {
+                // before the empty lines separating the keys
+                let mut end = self.get_section_end(header);
+                while end > header + 1 && self.lines[end - 1].trim().is_empty() {
+                    end -= 1;
+                }
+                self.lines.insert(end, line);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remove the values of the key matching `predicate` and return how many were removed
+    pub fn remove_values(&mut self, key: &str, predicate: impl Fn(&str) -> bool) -> usize {
+        let to_remove: Vec<(usize, usize)> = self
+            .get_entries(key)
+            .into_iter()
+            .filter(|(start, end)| {
+                parse_value(&self.lines[*start..*end].join("\n"))
+                    .is_some_and(|v| predicate(&v.name))
+            })
+            .collect();
+
+        // from the end so the indexes stay valid
+        for (start, end) in to_remove.iter().rev() {
+            self.lines.drain(*start..*end);
+        }
+
+        to_remove.len()
+    }
+
+    // the names of the keys are escaped like the strings, and followed by a timestamp
+    fn find_key(&self, key: &str) -> Option<usize> {
+        let header = format!("[{}]", escape(key)).to_lowercase();
+        self.lines
+            .iter()
+            .position(|l| l.to_lowercase().starts_with(&header))
+    }
+
+    fn get_section_end(&self, header: usize) -> usize {
+        self.lines[header + 1..]
+            .iter()
+            .position(|l| l.starts_with('['))
+            .map(|i| header + 1 + i)
+            .unwrap_or(self.lines.len())
+    }
+
+    // the lines of each value of the key, long values continue on the next lines with a `\`
+    fn get_entries(&self, key: &str) -> Vec<(usize, usize)> {
+        let Some(header) = self.find_key(key) else {
+            return vec![];
+        };
+
+        let section_end = self.get_section_end(header);
+        let mut entries = vec![];
+        let mut i = header + 1;
+
+        while i < section_end {
+            let start = i;
+            while i < section_end && self.lines[i].ends_with('\\') {
+                i += 1;
+            }
+            i = (i + 1).min(section_end);
+
+            if self.lines[start].starts_with('"') {
+                entries.push((start, i));
+            }
+        }
+
+        entries
+    }
+}
+
+impl Display for RegistryFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for RegistryData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistryData::String(value) => write!(f, "\"{}\"", escape(value)),
+            RegistryData::Dword(value) => write!(f, "dword:{:08x}", value),
+            RegistryData::Hex { kind, bytes } => {
+                let bytes = bytes
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect::<Vec<String>>()
+                    .join(",");
+
+                if *kind == REG_BINARY {
+                    write!(f, "hex:{}", bytes)
+                } else {
+                    write!(f, "hex({:x}):{}", kind, bytes)
+                }
+            }
+            RegistryData::Raw(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+fn parse_value(entry: &str) -> Option<RegistryValue> {
+    let (name, data) = read_quoted(entry)?;
+    let data = data.strip_prefix('=')?;
+
+    // the continued lines are joined back
+    let data = data.replace("\\\n", "");
+
+    let data = if data.starts_with('"') {
+        RegistryData::String(read_quoted(&data)?.0)
+    } else if let Some(value) = data.strip_prefix("dword:") {
+        RegistryData::Dword(u32::from_str_radix(value, 16).ok()?)
+    } else if let Some(value) = data.strip_prefix("hex:") {
+        RegistryData::Hex {
+            kind: REG_BINARY,
+            bytes: parse_hex(value)?,
+        }
+    } else if let Some((kind, value)) = data.strip_prefix("hex(").and_then(|d| d.split_once("):")) {
+        RegistryData::Hex {
+            kind: u32::from_str_radix(kind, 16).ok()?,
+            bytes: parse_hex(value)?,
+        }
+    } else {
+        RegistryData::Raw(data)
+    };
+
+    Some(RegistryValue { name, data })
+}
+
+fn parse_hex(value: &str) -> Option<Vec<u8>> {
+    if value.is_empty() {
+        return Some(vec![]);
+    }
+
+    value
+        .split(',')
+        .map(|b| u8::from_str_radix(b.trim(), 16).ok())
+        .collect()
+}
+
+// return the unescaped string and what's after it
+fn read_quoted(s: &str) -> Option<(String, &str)> {
+    let mut chars = s.strip_prefix('"')?.char_indices();
+    let mut value = String::new();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &s[i + 2..])),
+            '\\' => match chars.next()?.1 {
+                'n' => value.push('\n'),
+                'r' => value.push('\r'),
+                't' => value.push('\t'),
+                '0' => value.push('\0'),
+                c => value.push(c),
+            },
+            c => value.push(c),
+        }
+    }
+
+    None
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static USER_REG: &str = r#"WINE REGISTRY Version 2
+;; All keys relative to \\User\\S-1-5-21-0-0-0-1000
+
+#arch=win64
+
+[Control Panel\\Desktop] 1700000000
+#time=1da1c2b3c4d5e6f
+"LogPixels"=dword:00000060
+"Wallpaper"=""
+
+[Software\\Kuro Games\\Game] 1700000001
+#time=1da1c2b3c4d5e70
+"Language_h2060000000"=hex:45,6e,67,6c,69,73,68,00
+"Screenmanager Resolution Width_h182942802"=dword:00000780
+"UnityGraphicsQuality_h1669003810"=hex:00,00,00,00,00,00,00,00,00,00,00,00,00,\
+  00,00,00,00,00,00,00,00,00,00,00,00,00,00,00,00,00,00,00,00
+"Path"=str(2):"C:\\users\\\"steamuser\""
+
+[Software\\Wine\\DllOverrides] 1700000002
+#time=1da1c2b3c4d5e71
+"d3d11"="native"
+"#;
+
+    static KEY: &str = r"Software\Kuro Games\Game";
+
+    #[test]
+    fn round_trip() {
+        assert_eq!(RegistryFile::parse(USER_REG).to_string(), USER_REG);
+    }
+
+    #[test]
+    fn read_values() {
+        let registry = RegistryFile::parse(USER_REG);
+
+        assert!(registry.has_key(r"control panel\desktop"));
+        assert!(!registry.has_key(r"Software\Kuro Games"));
+
+        let values = registry.get_values(KEY);
+        assert_eq!(values.len(), 4);
+        assert_eq!(
+            values[0],
+            RegistryValue {
+                name: "Language_h2060000000".to_string(),
+                data: RegistryData::Hex {
+                    kind: REG_BINARY,
+                    bytes: b"English\0".to_vec(),
+                },
+            }
+        );
+        assert_eq!(values[1].data, RegistryData::Dword(1920));
+        assert_eq!(
+            values[2].data,
+            RegistryData::Hex {
+                kind: REG_BINARY,
+                bytes: vec![0; 33],
+            }
+        );
+        assert_eq!(
+            values[3].data,
+            RegistryData::Raw(r#"str(2):"C:\\users\\\"steamuser\"""#.to_string())
+        );
+
+        assert_eq!(
+            registry.get_values(r"Control Panel\Desktop")[1].data,
+            RegistryData::String(String::new())
+        );
+    }
+
+    #[test]
+    fn edit_values() {
+        let mut registry = RegistryFile::parse(USER_REG);
+
+        registry
+            .set_value(
+                KEY,
+                "Screenmanager Resolution Width_h182942802",
+                &RegistryData::Dword(2560),
+            )
+            .unwrap();
+        registry
+            .set_value(KEY, "Quote \"\\", &RegistryData::String("a\nb".to_string()))
+            .unwrap();
+        assert_eq!(
+            registry.remove_values(KEY, |name| name.starts_with("UnityGraphicsQuality")),
+            1
+        );
+        assert!(registry
+            .set_value(r"Software\Missing", "Value", &RegistryData::Dword(1))
+            .is_err());
+
+        let content = registry.to_string();
+        assert!(content.contains("\"Screenmanager Resolution Width_h182942802\"=dword:00000a00\n"));
+        assert!(content.contains(
+            "\"Path\"=str(2):\"C:\\\\users\\\\\\\"steamuser\\\"\"\n\"Quote \\\"\\\\\"=\"a\\nb\"\n\n[Software\\\\Wine\\\\DllOverrides]"
+        ));
+        assert!(!content.contains("UnityGraphicsQuality"));
+
+        // the other keys are kept as they were
+        let registry = RegistryFile::parse(&content);
+        assert_eq!(
+            registry.get_values(KEY)[3],
+            RegistryValue {
+                name: "Quote \"\\".to_string(),
+                data: RegistryData::String("a\nb".to_string()),
+            }
+        );
+        assert_eq!(
+            registry.get_values(r"Software\Wine\DllOverrides")[0].data,
+            RegistryData::String("native".to_string())
+        );
+    }
+}
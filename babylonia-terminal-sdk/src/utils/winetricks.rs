@@ -0,0 +1,127 @@
+use std::{
+    env,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use log::{debug, info};
+use tokio::{
+    fs::{rename, set_permissions, File, OpenOptions},
+    io::AsyncWriteExt,
+};
+use wincompatlib::wine::Wine;
+
+use crate::game_config::GameConfig;
+
+pub static WINETRICKS_VERSION: &str = "20250102";
+
+fn get_download_url() -> String {
+    format!(
+        "https://raw.githubusercontent.com/Winetricks/winetricks/{}/src/winetricks",
+        WINETRICKS_VERSION
+    )
+}
+
+pub async fn get_log_file_path() -> PathBuf {
+    GameConfig::get_config_directory()
+        .await
+        .join("winetricks.log")
+}
+
+/// Find winetricks in this order : the path set in the config, the $PATH,
+/// then the pinned copy in the config directory, which is downloaded if needed.
+pub async fn get_winetricks() -> anyhow::Result<PathBuf> {
+    if let Some(path) = GameConfig::get_config().await.winetricks_path {
+        if !path.is_file() {
+            anyhow::bail!(
+                "The winetricks set in the config doesn't exist : {}",
+                path.to_str().unwrap()
+            );
+        }
+
+        return Ok(path);
+    }
+
+    if let Some(path) = find_in_path("winetricks") {
+        debug!("Using winetricks from the $PATH : {:?}", path);
+        return Ok(path);
+    }
+
+    let path = GameConfig::get_config_directory().await.join("winetricks");
+    if !path.is_file() {
+        download(&path).await?;
+    }
+
+    Ok(path)
+}
+
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+
+    env::split_paths(&paths)
+        .map(|dir| dir.join(name))
+        .find(|path| {
+            path.metadata()
+                .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+                .unwrap_or(false)
+        })
+}
+
+async fn download(path: &Path) -> anyhow::Result<()> {
+    info!("Downloading winetricks {}...", WINETRICKS_VERSION);
+
+    let response = reqwest::get(get_download_url()).await?.error_for_status()?;
+    let content = response.bytes().await?;
+
+    let tmp_path = path.with_extension("part");
+    let mut file = File::create(&tmp_path).await?;
+    file.write_all(&content).await?;
+    file.flush().await?;
+
+    set_permissions(&tmp_path, std::fs::Permissions::from_mode(0o755)).await?;
+    rename(tmp_path, path).await?;
+
+    Ok(())
+}
+
+pub async fn install(wine: &Wine, verb: &str) -> anyhow::Result<()> {
+    let winetricks = get_winetricks().await?;
+    debug!("Installing {} with {:?}", verb, winetricks);
+
+    let mut command = Command::new(&winetricks);
+    command
+        .arg("-q")
+        .arg(verb)
+        .envs(wine.get_envs())
+        .env("WINE", &wine.binary);
+
+    let output = tokio::task::spawn_blocking(move || command.output()).await??;
+
+    let log_file_path = get_log_file_path().await;
+    let mut log_file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_file_path)
+        .await?;
+    log_file
+        .write_all(format!("--- winetricks -q {} ---\n", verb).as_bytes())
+        .await?;
+    log_file.write_all(&output.stdout).await?;
+    log_file.write_all(&output.stderr).await?;
+
+    // some verbs are aborting without changing the exit code
+    let aborted = String::from_utf8_lossy(&output.stderr).contains("Aborting.")
+        || String::from_utf8_lossy(&output.stdout).contains("Aborting.");
+
+    if !output.status.success() || aborted {
+        anyhow::bail!(
+            "winetricks failed to install {} ({}), see {} for more details",
+            verb,
+            output.status,
+            log_file_path.to_str().unwrap()
+        );
+    }
+
+    Ok(())
+}